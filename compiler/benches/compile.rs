@@ -0,0 +1,16 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use ziraffe_compiler::compiler::compile_program;
+use ziraffe_compiler::testutil::generate_contract;
+use ziraffe_parser::parser;
+
+fn bench_compile(c: &mut Criterion) {
+    for &(functions, depth) in &[(10, 2), (100, 4), (50, 16)] {
+        let program = parser::parse_program(&generate_contract(functions, depth)).unwrap();
+        c.bench_function(&format!("compile {}x{}", functions, depth), |b| {
+            b.iter(|| compile_program(black_box(&program)).unwrap())
+        });
+    }
+}
+
+criterion_group!(benches, bench_compile);
+criterion_main!(benches);
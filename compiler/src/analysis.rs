@@ -0,0 +1,65 @@
+use crate::error::CycleError;
+use crate::symbol_table::Contract;
+use indexmap::map::IndexMap;
+
+#[derive(Clone, Copy, PartialEq)]
+enum Mark {
+    Visiting,
+    Done,
+}
+
+/// Order contracts so each comes after the contracts it depends on.
+/// Independent contracts keep their declaration order.
+pub fn topo_order(contracts: &IndexMap<String, Contract>) -> Result<Vec<String>, CycleError> {
+    let mut marks = IndexMap::new();
+    let mut order = vec![];
+    for name in contracts.keys() {
+        visit(name, contracts, &mut marks, &mut vec![], &mut order)?;
+    }
+    Ok(order)
+}
+
+/// The other contracts a contract needs, through the libraries it attaches with `using`.
+pub fn dependencies<'a>(
+    contract: &'a Contract,
+    contracts: &IndexMap<String, Contract>,
+) -> Vec<&'a str> {
+    let mut dependencies = vec![];
+    for (library, _) in &contract.using {
+        if *library != contract.name
+            && contracts.contains_key(library)
+            && !dependencies.contains(&library.as_str())
+        {
+            dependencies.push(library.as_str());
+        }
+    }
+    dependencies
+}
+
+fn visit<'a>(
+    name: &'a str,
+    contracts: &'a IndexMap<String, Contract>,
+    marks: &mut IndexMap<&'a str, Mark>,
+    path: &mut Vec<&'a str>,
+    order: &mut Vec<String>,
+) -> Result<(), CycleError> {
+    match marks.get(name) {
+        Some(Mark::Done) => return Ok(()),
+        Some(Mark::Visiting) => {
+            let start = path.iter().position(|visited| *visited == name).unwrap();
+            let mut cycle: Vec<String> = path[start..].iter().map(|n| n.to_string()).collect();
+            cycle.push(name.to_string());
+            return Err(CycleError { contracts: cycle });
+        }
+        None => {}
+    }
+    marks.insert(name, Mark::Visiting);
+    path.push(name);
+    for dependency in dependencies(&contracts[name], contracts) {
+        visit(dependency, contracts, marks, path, order)?;
+    }
+    path.pop();
+    marks.insert(name, Mark::Done);
+    order.push(name.to_string());
+    Ok(())
+}
@@ -0,0 +1,252 @@
+//! Minimal WebAssembly lowering for running pure arithmetic off-chain.
+//!
+//! Every `uint` is an `i64` local, so values above `u64::MAX` are truncated
+//! and arithmetic wraps instead of matching 256-bit semantics.
+
+use crate::instruction::Instruction;
+use crate::symbol_table::{Contract, Function, Symbol, Type};
+use std::collections::HashMap;
+
+const MAGIC: &[u8] = b"\0asm";
+const VERSION: &[u8] = &[1, 0, 0, 0];
+
+const SECTION_TYPE: u8 = 1;
+const SECTION_FUNCTION: u8 = 3;
+const SECTION_EXPORT: u8 = 7;
+const SECTION_CODE: u8 = 10;
+
+const FUNC_TYPE: u8 = 0x60;
+const I64: u8 = 0x7e;
+const EXPORT_FUNC: u8 = 0x00;
+
+const LOCAL_GET: u8 = 0x20;
+const LOCAL_SET: u8 = 0x21;
+const I64_CONST: u8 = 0x42;
+const I64_ADD: u8 = 0x7c;
+const I64_SUB: u8 = 0x7d;
+const I64_MUL: u8 = 0x7e;
+const I64_DIV_U: u8 = 0x80;
+const I64_REM_U: u8 = 0x82;
+const END: u8 = 0x0b;
+
+fn uses_member(contract: &Contract, function: &Function) -> bool {
+    function
+        .result
+        .iter()
+        .any(|result| contract.is_member(result))
+        || function
+            .codes
+            .all_instructions()
+            .into_iter()
+            .any(|instruction| {
+                instruction
+                    .operands()
+                    .into_iter()
+                    .chain(instruction.dst())
+                    .any(|symbol| contract.is_member(symbol))
+            })
+}
+
+/// Emit a module exporting every function of the contract that only does
+/// `uint` arithmetic. Other functions are skipped.
+pub fn emit(contract: &Contract) -> Vec<u8> {
+    let lowered: Vec<(&Function, Vec<u8>)> = contract
+        .functions
+        .values()
+        .filter_map(|function| lower_function(contract, function).map(|body| (function, body)))
+        .collect();
+
+    let mut types = vec![];
+    let mut funcs = vec![];
+    let mut exports = vec![];
+    let mut codes = vec![];
+    for (index, (function, body)) in lowered.iter().enumerate() {
+        let mut func_type = vec![FUNC_TYPE];
+        write_u32(&mut func_type, function.params.len() as u32);
+        func_type.extend(function.params.iter().map(|_| I64));
+        if function.ret == Type::Uint {
+            func_type.extend(&[1, I64]);
+        } else {
+            func_type.push(0);
+        }
+        types.push(func_type);
+
+        let mut func = vec![];
+        write_u32(&mut func, index as u32);
+        funcs.push(func);
+
+        let mut export = vec![];
+        write_name(&mut export, &function.name);
+        export.push(EXPORT_FUNC);
+        write_u32(&mut export, index as u32);
+        exports.push(export);
+
+        let mut code = vec![];
+        write_u32(&mut code, body.len() as u32);
+        code.extend(body);
+        codes.push(code);
+    }
+
+    let mut module = vec![];
+    module.extend(MAGIC);
+    module.extend(VERSION);
+    write_section(&mut module, SECTION_TYPE, &types);
+    write_section(&mut module, SECTION_FUNCTION, &funcs);
+    write_section(&mut module, SECTION_EXPORT, &exports);
+    write_section(&mut module, SECTION_CODE, &codes);
+    module
+}
+
+/// Lower the body of a function, or `None` if it does more than `uint` arithmetic.
+/// Members live in contract storage, so functions touching them are skipped.
+fn lower_function(contract: &Contract, function: &Function) -> Option<Vec<u8>> {
+    if function.params.iter().any(|param| param.typ != Type::Uint)
+        || !matches!(function.ret, Type::Uint | Type::None)
+        || !function.is_pure(contract)
+        || uses_member(contract, function)
+    {
+        return None;
+    }
+    let mut locals = Locals::default();
+    for param in &function.params {
+        locals.index(param);
+    }
+    let params = locals.len();
+
+    let mut expr = vec![];
+    for instruction in function.codes.instructions() {
+        match instruction {
+            Instruction::Add { dst, left, right }
+            | Instruction::Sub { dst, left, right }
+            | Instruction::Mul { dst, left, right }
+            | Instruction::Div { dst, left, right }
+            | Instruction::Mod { dst, left, right } => {
+                locals.load(&mut expr, left)?;
+                locals.load(&mut expr, right)?;
+                expr.push(match instruction {
+                    Instruction::Add { .. } => I64_ADD,
+                    Instruction::Sub { .. } => I64_SUB,
+                    Instruction::Mul { .. } => I64_MUL,
+                    Instruction::Div { .. } => I64_DIV_U,
+                    _ => I64_REM_U,
+                });
+                locals.store(&mut expr, dst)?;
+            }
+            Instruction::Assign { dst, src } | Instruction::InitAssign { name: dst, src } => {
+                locals.load(&mut expr, src)?;
+                locals.store(&mut expr, dst)?;
+            }
+            // Locals start zeroed.
+            Instruction::Init { name } if name.typ == Type::Uint => {}
+            _ => return None,
+        }
+    }
+    if function.ret == Type::Uint {
+        locals.load(&mut expr, function.result.as_ref()?)?;
+    }
+    expr.push(END);
+
+    let mut body = vec![];
+    let declared = locals.len() - params;
+    if declared == 0 {
+        write_u32(&mut body, 0);
+    } else {
+        write_u32(&mut body, 1);
+        write_u32(&mut body, declared);
+        body.push(I64);
+    }
+    body.extend(expr);
+    Some(body)
+}
+
+#[derive(Default)]
+struct Locals {
+    indices: HashMap<(String, u32), u32>,
+}
+
+impl Locals {
+    fn len(&self) -> u32 {
+        self.indices.len() as u32
+    }
+
+    fn index(&mut self, symbol: &Symbol) -> u32 {
+        let next = self.len();
+        *self
+            .indices
+            .entry((symbol.id.clone(), symbol.num))
+            .or_insert(next)
+    }
+
+    fn load(&mut self, expr: &mut Vec<u8>, symbol: &Symbol) -> Option<()> {
+        if symbol.typ != Type::Uint {
+            return None;
+        }
+        if let Some(value) = &symbol.value {
+            // Truncated to the low 64 bits.
+            let low = value
+                .to_bytes_le()
+                .iter()
+                .take(8)
+                .rev()
+                .fold(0u64, |low, byte| low << 8 | u64::from(*byte));
+            expr.push(I64_CONST);
+            write_i64(expr, low as i64);
+        } else {
+            expr.push(LOCAL_GET);
+            let index = self.index(symbol);
+            write_u32(expr, index);
+        }
+        Some(())
+    }
+
+    fn store(&mut self, expr: &mut Vec<u8>, symbol: &Symbol) -> Option<()> {
+        if symbol.typ != Type::Uint || symbol.value.is_some() {
+            return None;
+        }
+        expr.push(LOCAL_SET);
+        let index = self.index(symbol);
+        write_u32(expr, index);
+        Some(())
+    }
+}
+
+fn write_section(module: &mut Vec<u8>, id: u8, entries: &[Vec<u8>]) {
+    let mut content = vec![];
+    write_u32(&mut content, entries.len() as u32);
+    for entry in entries {
+        content.extend(entry);
+    }
+    module.push(id);
+    write_u32(module, content.len() as u32);
+    module.extend(content);
+}
+
+fn write_name(out: &mut Vec<u8>, name: &str) {
+    write_u32(out, name.len() as u32);
+    out.extend(name.as_bytes());
+}
+
+fn write_u32(out: &mut Vec<u8>, mut value: u32) {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            out.push(byte);
+            return;
+        }
+        out.push(byte | 0x80);
+    }
+}
+
+fn write_i64(out: &mut Vec<u8>, mut value: i64) {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        let done = (value == 0 && byte & 0x40 == 0) || (value == -1 && byte & 0x40 != 0);
+        if done {
+            out.push(byte);
+            return;
+        }
+        out.push(byte | 0x80);
+    }
+}
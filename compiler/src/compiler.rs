@@ -1,36 +1,146 @@
-use crate::error::{CompileError, CompileErrorType};
+use crate::error::{CompileError, CompileErrorType, CompileNote, CompileWarning};
 use crate::instruction::{Block, Instruction};
 use crate::symbol_table::{Context, Contract, Function, Symbol, Type};
 use indexmap::map::IndexMap;
 use num_bigint::BigUint;
+use std::collections::HashSet;
 use ziraffe_parser::ast;
 use ziraffe_parser::ast::{ExpressionType, Program, StatementType};
+use ziraffe_parser::location::Location;
+use ziraffe_parser::parser;
 
-pub fn compile_program(program: &ast::Program) -> CompileResult<IndexMap<String, Contract>> {
+/// Everything a successful compilation produces, fatal errors aside.
+#[derive(Debug, Default)]
+pub struct CompileResult {
+    pub contracts: IndexMap<String, Contract>,
+    pub free_functions: IndexMap<String, Function>,
+    // `pragma key value;` directives in source order.
+    pub pragmas: Vec<(String, String)>,
+    pub warnings: Vec<CompileWarning>,
+    pub notes: Vec<CompileNote>,
+}
+
+pub fn compile(program: &ast::Program) -> Result<CompileResult> {
+    let mut compiler = Compiler::new();
+    compiler.compile_program(program)?;
+    Ok(CompileResult {
+        contracts: compiler.contracts,
+        free_functions: compiler.free_functions,
+        pragmas: compiler.pragmas,
+        warnings: compiler.warnings,
+        notes: compiler.notes,
+    })
+}
+
+pub fn compile_program(program: &ast::Program) -> Result<IndexMap<String, Contract>> {
+    Ok(compile(program)?.contracts)
+}
+
+/// Compile a standalone expression and check that it has the `expected` type.
+pub fn check_expression(source: &str, expected: Type) -> Result<()> {
+    let expr = parser::parse_expression(source).map_err(|err| CompileError {
+        error: CompileErrorType::SyntaxError(err.error.to_string()),
+        location: err.location,
+    })?;
+    let expr = ast::desugar_expr(expr);
+    let mut compiler = Compiler::new();
+    compiler.context.add_block();
+    let value = compiler.compile_expr(&expr)?;
+    compiler.check_assign_type(
+        &Symbol::named(String::new(), expected),
+        &value,
+        expr.location,
+    )
+}
+
+/// Compile the program and also return a dump of the symbols visible at the end of each block.
+pub fn compile_program_with_symbols(
+    program: &ast::Program,
+) -> Result<(IndexMap<String, Contract>, String)> {
     let mut compiler = Compiler::new();
+    compiler.symbol_dump = Some(String::new());
     compiler.compile_program(program)?;
-    Ok(compiler.contracts)
+    Ok((compiler.contracts, compiler.symbol_dump.unwrap()))
+}
+
+/// Map every instruction of the contract's functions to its source location.
+/// Entries are keyed by the function's key in `contract.functions`, and instructions
+/// are indexed in order within each function, nested blocks included.
+pub fn source_map(contract: &Contract) -> Vec<(String, usize, Location)> {
+    let mut map = vec![];
+    for (key, function) in &contract.functions {
+        let mut index = 0;
+        map_block(key, &function.codes, &mut index, &mut map);
+    }
+    map
+}
+
+fn map_block(
+    key: &str,
+    block: &Block,
+    index: &mut usize,
+    map: &mut Vec<(String, usize, Location)>,
+) {
+    for (instruction, location) in block.instructions().iter().zip(block.locations()) {
+        map.push((key.to_string(), *index, *location));
+        *index += 1;
+        for block in instruction.blocks() {
+            map_block(key, block, index, map);
+        }
+    }
 }
 
 struct Compiler {
     contracts: IndexMap<String, Contract>,
+    free_functions: IndexMap<String, Function>,
+    pragmas: Vec<(String, String)>,
     context: Context,
+    symbol_dump: Option<String>,
+    warnings: Vec<CompileWarning>,
+    notes: Vec<CompileNote>,
+    // Declared return type of the function being compiled.
+    return_type: Option<Type>,
+    // False inside an `unchecked` block, where arithmetic wraps.
+    checked: bool,
 }
 
-type CompileResult<T> = Result<T, CompileError>;
+type Result<T> = std::result::Result<T, CompileError>;
 
 impl Compiler {
     fn new() -> Self {
         Compiler {
             contracts: Default::default(),
+            free_functions: Default::default(),
+            pragmas: vec![],
             context: Context::new(),
+            symbol_dump: None,
+            warnings: vec![],
+            notes: vec![],
+            return_type: None,
+            checked: true,
         }
     }
 
-    fn compile_program(&mut self, ast: &ast::Program) -> CompileResult<()> {
-        match ast {
+    fn compile_program(&mut self, ast: &ast::Program) -> Result<()> {
+        match ast::desugar(ast.clone()) {
             Program::GlobalStatements(stmts) => {
-                for stmt in stmts {
+                self.declare_free_functions(&stmts)?;
+                self.declare_contracts(&stmts)?;
+                for stmt in &stmts {
+                    // The parser only produces these, but a hand-built AST may not.
+                    if !matches!(
+                        stmt.node,
+                        StatementType::ContractStatement { .. }
+                            | StatementType::FunctionStatement { .. }
+                            | StatementType::PragmaStatement { .. }
+                    ) {
+                        return Err(CompileError {
+                            error: CompileErrorType::SyntaxError(String::from(
+                                "Expected a contract, function or pragma at the top level",
+                            )),
+                            location: stmt.location,
+                        });
+                    }
                     self.compile_stmt(stmt)?;
                 }
             }
@@ -38,7 +148,7 @@ impl Compiler {
         Ok(())
     }
 
-    fn compile_stmt(&mut self, stmt: &ast::Statement) -> CompileResult<Symbol> {
+    fn compile_stmt(&mut self, stmt: &ast::Statement) -> Result<Symbol> {
         match &stmt.node {
             ast::StatementType::FunctionStatement {
                 function_name,
@@ -46,93 +156,209 @@ impl Compiler {
                 expr,
                 returns,
             } => {
+                let is_member = self.context.is_member;
                 self.context.is_member = false;
-                let name = self.compile_expr(function_name)?;
+                let name = self.compile_name(function_name)?;
+                self.context.current_function = Some(name.clone());
+                // Parameters get their own scope around the body.
+                self.context.add_block();
                 let params = self.compile_param(parameters)?;
                 let typ = if let Some(ret) = returns {
                     Type::get_type(ret)
                 } else {
                     Type::None
                 };
-                let block = self.compile_block(expr)?;
-                self.add_function(&name.id, params, typ, block);
-                self.context.is_member = true;
-                Ok(Symbol::temp_symbol(&mut self.context))
+                let return_type = self.return_type.replace(typ.clone());
+                let (block, result) = self.compile_block_with_value(expr)?;
+                self.return_type = return_type;
+                self.pop_block();
+                self.add_function(&name, params, typ, block, result, stmt.location)?;
+                self.context.current_function = None;
+                self.context.is_member = is_member;
+                Symbol::temp_symbol(&mut self.context, stmt.location)
             }
             StatementType::ContractStatement {
                 contract_name,
                 members,
             } => {
-                let name = self.compile_expr(contract_name)?;
-                self.look_contract(&name.id);
-                self.context.is_member = true;
+                let name = self.compile_name(contract_name)?;
+                // Top-level contracts are already declared by `declare_contracts`.
+                if !self.contracts.contains_key(&name) {
+                    self.look_contract(&name);
+                    self.declare_members(members)?;
+                }
+                self.context.current_contract = Some(name);
+                self.context.add_block();
                 self.compile_stmt(members)?;
-                self.context.is_member = false;
-                Ok(Symbol::temp_symbol(&mut self.context))
+                let constructor = self.pop_block();
+                self.current_contract(stmt.location)?.constructor = constructor;
+                self.context.current_contract = None;
+                Symbol::temp_symbol(&mut self.context, stmt.location)
             }
             StatementType::InitializerStatement {
                 variable_type,
+                mutability,
                 variable,
                 default,
+                ..
             } => {
-                let typ = Type::get_type(variable_type);
-                let mut symbol = self.compile_expr(variable)?;
-                if symbol.typ == Type::Undefined {
-                    symbol.typ = typ;
+                // A declaration shadows any member or outer binding of the same name.
+                let symbol = Symbol {
+                    mutability: mutability.clone(),
+                    ..Symbol::named(self.compile_name(variable)?, Type::get_type(variable_type))
+                };
+                if *mutability == ast::Mutability::Constant
+                    && !default
+                        .as_ref()
+                        .is_some_and(|value| self.is_constant_expr(value))
+                {
+                    return Err(CompileError {
+                        error: CompileErrorType::TypeError(format!(
+                            "Constant `{}` must be initialized with a compile-time constant",
+                            symbol.id
+                        )),
+                        location: stmt.location,
+                    });
                 }
                 if self.context.is_member {
-                    let contract_name = self.context.current_contract.as_ref().unwrap();
-                    let contract = self.contracts.get_mut(contract_name).unwrap();
-                    contract
+                    if self.context.current_contract.is_none() {
+                        return Err(CompileError {
+                            error: CompileErrorType::SyntaxError(format!(
+                                "Member `{}` declared outside a contract",
+                                symbol.id
+                            )),
+                            location: stmt.location,
+                        });
+                    }
+                    self.current_contract(stmt.location)?
                         .member
                         .insert(symbol.id.to_string(), symbol.clone());
+                    if let Some(value) = default {
+                        let src = self.compile_expr(value)?;
+                        self.check_assign_type(&symbol, &src, value.location)?;
+                        self.context.add_instruction(
+                            Instruction::InitAssign {
+                                name: Box::new(symbol.clone()),
+                                src: Box::new(src),
+                            },
+                            stmt.location,
+                        );
+                    }
                 } else {
                     if let Some(value) = default {
-                        // TODO:
                         let src = self.compile_expr(value)?;
-                        self.context.add_instruction(Instruction::InitAssign {
-                            name: Box::new(symbol.clone()),
-                            src: Box::new(src),
-                        });
+                        self.check_assign_type(&symbol, &src, value.location)?;
+                        self.context.add_instruction(
+                            Instruction::InitAssign {
+                                name: Box::new(symbol.clone()),
+                                src: Box::new(src),
+                            },
+                            stmt.location,
+                        );
                     } else {
-                        self.context.add_instruction(Instruction::Init {
-                            name: Box::new(symbol.clone()),
-                        });
+                        self.context.add_instruction(
+                            Instruction::Init {
+                                name: Box::new(symbol.clone()),
+                            },
+                            stmt.location,
+                        );
                     }
-                    self.context.add_symbol(symbol.id.as_str(), symbol.clone());
+                    self.context
+                        .add_local(symbol.id.as_str(), symbol.clone(), stmt.location);
                 }
                 Ok(symbol)
             }
+            StatementType::PragmaStatement { key, value } => {
+                self.pragmas.push((key.clone(), value.clone()));
+                Symbol::temp_symbol(&mut self.context, stmt.location)
+            }
+            StatementType::UsingStatement { library, .. } => {
+                // Attached up front by `declare_members`.
+                let name = self.compile_name(library)?;
+                if !self.contracts.contains_key(&name) {
+                    return Err(CompileError {
+                        error: CompileErrorType::NameError(format!("Undefined library `{}`", name)),
+                        location: library.location,
+                    });
+                }
+                Symbol::temp_symbol(&mut self.context, stmt.location)
+            }
             StatementType::MemberStatement { statements } => {
+                let is_member = std::mem::replace(&mut self.context.is_member, true);
                 for statement in statements {
                     self.compile_stmt(statement)?;
                 }
-                Ok(Symbol::temp_symbol(&mut self.context))
+                self.context.is_member = is_member;
+                Symbol::temp_symbol(&mut self.context, stmt.location)
             }
             StatementType::Expression { expression } => self.compile_expr(expression),
+            StatementType::Return { value } => {
+                let function = self.context.current_function.clone().unwrap_or_default();
+                let expected = self.return_type.clone().ok_or_else(|| CompileError {
+                    error: CompileErrorType::SyntaxError(String::from("Return outside a function")),
+                    location: stmt.location,
+                })?;
+                let value = match value {
+                    Some(value) => Some(self.compile_expr(value)?),
+                    None => None,
+                };
+                let message = match &value {
+                    Some(_) if expected == Type::None => {
+                        Some(format!("Function `{}` does not return a value", function))
+                    }
+                    Some(value) if expected.join(&value.typ) != Some(expected.clone()) => {
+                        Some(format!(
+                            "Function `{}` returns {:?} but got {:?}",
+                            function, expected, value.typ
+                        ))
+                    }
+                    None if expected != Type::None => Some(format!(
+                        "Function `{}` must return {:?}",
+                        function, expected
+                    )),
+                    _ => None,
+                };
+                if let Some(message) = message {
+                    return Err(CompileError {
+                        error: CompileErrorType::TypeError(message),
+                        location: stmt.location,
+                    });
+                }
+                self.context.add_instruction(
+                    Instruction::Return {
+                        value: value.map(Box::new),
+                    },
+                    stmt.location,
+                );
+                Symbol::temp_symbol(&mut self.context, stmt.location)
+            }
         }
     }
 
-    fn compile_expr(&mut self, expr: &ast::Expression) -> CompileResult<Symbol> {
+    fn compile_expr(&mut self, expr: &ast::Expression) -> Result<Symbol> {
         match &expr.node {
-            ast::ExpressionType::CompoundExpression {
-                statements: _,
-                return_value: _,
-            } => {
-                self.compile_block(expr)?;
-                Ok(Symbol::temp_symbol(&mut self.context))
+            ast::ExpressionType::CompoundExpression { .. } => {
+                // The block runs in place; its trailing expression is the value.
+                let (block, value) = self.compile_block_with_value(expr)?;
+                self.context.append_block(block);
+                match value {
+                    Some(value) => Ok(value),
+                    None => Symbol::temp_symbol(&mut self.context, expr.location),
+                }
             }
-            ExpressionType::AssignExpression {
-                left,
-                operator: _,
-                right,
-            } => {
+            // `a op= b` was desugared to `a = a op b`.
+            ExpressionType::AssignExpression { left, right, .. } => {
                 let a = self.compile_expr(left)?;
+                self.check_mutable(&a, left.location)?;
                 let b = self.compile_expr(right)?;
-                self.context.add_instruction(Instruction::Assign {
-                    dst: Box::new(a.clone()),
-                    src: Box::new(b),
-                });
+                self.check_assign_type(&a, &b, expr.location)?;
+                self.context.add_instruction(
+                    Instruction::Assign {
+                        dst: Box::new(a.clone()),
+                        src: Box::new(b),
+                    },
+                    expr.location,
+                );
                 Ok(a)
             }
             ExpressionType::BinaryExpression {
@@ -142,30 +368,69 @@ impl Compiler {
             } => {
                 let a = self.compile_expr(left)?;
                 let b = self.compile_expr(right)?;
-                let dst =
-                    Symbol::result_symbol(&mut self.context, a.clone(), b.clone(), expr.location)?;
-                let res = Instruction::get_instruction_from_bin_op(
-                    operator.clone(),
-                    dst.clone(),
-                    a,
-                    b,
-                    expr.location,
-                )?;
-                self.context.add_instruction(res);
+                self.compile_binary(operator, a, b, expr.location)
+            }
+            ExpressionType::UnaryExpression { operator, operand } => {
+                let src = self.compile_expr(operand)?;
+                let (typ, sign) = match operator {
+                    ast::UnaryOperator::Neg => (Type::Uint, "-"),
+                    ast::UnaryOperator::Not => (Type::Bool, "!"),
+                };
+                if src.typ != typ {
+                    return Err(CompileError {
+                        error: CompileErrorType::TypeError(format!(
+                            "Operator `{}` expects {:?} but got {:?}",
+                            sign, typ, src.typ
+                        )),
+                        location: operand.location,
+                    });
+                }
+                let dst = Symbol::temp_symbol(&mut self.context, expr.location)?.with_type(typ);
+                let (dst_box, src) = (Box::new(dst.clone()), Box::new(src));
+                let instruction = match operator {
+                    ast::UnaryOperator::Neg => Instruction::Neg { dst: dst_box, src },
+                    ast::UnaryOperator::Not => Instruction::Not { dst: dst_box, src },
+                };
+                self.context.add_instruction(instruction, expr.location);
                 Ok(dst)
             }
             ExpressionType::FunctionCallExpression {
                 function_name,
                 arguments,
             } => {
-                let name = self.compile_expr(function_name)?;
                 let args = self.compile_param(arguments)?;
-                let res = Symbol::temp_symbol(&mut self.context);
-                self.context.add_instruction(Instruction::Call {
-                    dst: Box::new(res.clone()),
-                    func: Box::new(name),
-                    args,
-                });
+                let name = self.compile_function_name(function_name, &args)?;
+                let res = Symbol::temp_symbol(&mut self.context, expr.location)?
+                    .with_type(name.typ.clone());
+                self.context.add_instruction(
+                    Instruction::Call {
+                        dst: Box::new(res.clone()),
+                        func: Box::new(name),
+                        args,
+                    },
+                    expr.location,
+                );
+                Ok(res)
+            }
+            ExpressionType::MethodCallExpression {
+                object,
+                method,
+                arguments,
+            } => {
+                let object = self.compile_expr(object)?;
+                let mut args = vec![object];
+                args.extend(self.compile_param(arguments)?);
+                let name = self.compile_method_name(method, &args)?;
+                let res = Symbol::temp_symbol(&mut self.context, expr.location)?
+                    .with_type(name.typ.clone());
+                self.context.add_instruction(
+                    Instruction::Call {
+                        dst: Box::new(res.clone()),
+                        func: Box::new(name),
+                        args,
+                    },
+                    expr.location,
+                );
                 Ok(res)
             }
             ExpressionType::IfExpression {
@@ -173,44 +438,196 @@ impl Compiler {
                 if_expr,
                 else_expr,
             } => {
+                self.warn_assign_condition(condition);
                 let condition = self.compile_expr(condition)?;
-                let if_block = self.compile_block(if_expr)?;
-                self.context.add_instruction(Instruction::If {
-                    cond: Box::new(condition.clone()),
-                    block: Box::new(if_block),
-                });
+                let (mut if_block, if_value) = self.compile_block_with_value(if_expr)?;
+                let mut res = Symbol::temp_symbol(&mut self.context, expr.location)?;
+                let mut else_block = None;
                 if let Some(else_expression) = else_expr {
-                    let else_block = self.compile_block(else_expression)?;
-                    self.context.add_instruction(Instruction::Else {
-                        cond: Box::new(condition),
-                        block: Box::new(else_block),
-                    });
+                    let (mut block, else_value) = self.compile_else_branch(else_expression)?;
+                    // With both branches valued, the `if` yields their joined type.
+                    if let (Some(a), Some(b)) = (if_value, else_value) {
+                        res.set_type(a.typ.join(&b.typ).ok_or_else(|| CompileError {
+                            error: CompileErrorType::TypeError(format!(
+                                "If branches have different types: {:?} and {:?}",
+                                a.typ, b.typ
+                            )),
+                            location: expr.location,
+                        })?);
+                        for (block, value, location) in [
+                            (&mut if_block, a, if_expr.location),
+                            (&mut block, b, else_expression.location),
+                        ] {
+                            if value.typ != Type::Never {
+                                block.add_instruction(
+                                    Instruction::Assign {
+                                        dst: Box::new(res.clone()),
+                                        src: Box::new(value),
+                                    },
+                                    location,
+                                );
+                            }
+                        }
+                    }
+                    else_block = Some(block);
                 }
-                Ok(Symbol::temp_symbol(&mut self.context))
+                self.context.add_instruction(
+                    Instruction::If {
+                        cond: Box::new(condition),
+                        then_block: Box::new(if_block),
+                        else_block: else_block.map(Box::new),
+                    },
+                    expr.location,
+                );
+                Ok(res)
             }
             ExpressionType::ForEachExpression {
                 iterator,
                 vector,
                 for_expr,
             } => {
-                let iter = self.compile_expr(iterator)?;
-                let (start, end) = self.compile_range(vector)?;
+                let iter = Symbol::named(self.compile_name(iterator)?, Type::Uint);
+                if self.get_symbol(&iter.id, iterator.location).is_ok() {
+                    self.warnings.push(CompileWarning {
+                        message: format!("Loop iterator `{}` shadows an existing binding", iter.id),
+                        location: iterator.location,
+                    });
+                }
+                let (start, end, step) = self.compile_range(vector)?;
+                // The iterator is only visible inside the loop body.
+                self.context.add_block();
+                self.context.add_symbol(&iter.id, iter.clone());
                 let block = self.compile_block(for_expr)?;
-                self.context.add_instruction(Instruction::For {
-                    iter: Box::new(iter),
-                    start,
-                    end,
-                    block: Box::new(block),
-                });
-                Ok(Symbol::temp_symbol(&mut self.context))
+                self.pop_block();
+                self.context.add_instruction(
+                    Instruction::For {
+                        iter: Box::new(iter),
+                        start: Box::new(start),
+                        end: Box::new(end),
+                        step: Box::new(step),
+                        block: Box::new(block),
+                    },
+                    expr.location,
+                );
+                Symbol::temp_symbol(&mut self.context, expr.location)
+            }
+            ExpressionType::WhileExpression { condition, body } => {
+                self.warn_assign_condition(condition);
+                let cond = self.compile_expr(condition)?;
+                if cond.typ != Type::Bool {
+                    return Err(CompileError {
+                        error: CompileErrorType::TypeError(format!(
+                            "While condition must be Bool but got {:?}",
+                            cond.typ
+                        )),
+                        location: condition.location,
+                    });
+                }
+                let mut block = self.compile_block(body)?;
+                // Re-evaluate the condition into `cond` at the end of each iteration,
+                // without repeating its warnings.
+                let warnings = self.warnings.len();
+                self.context.add_block();
+                let again = self.compile_expr(condition)?;
+                if again != cond {
+                    self.context.add_instruction(
+                        Instruction::Assign {
+                            dst: Box::new(cond.clone()),
+                            src: Box::new(again),
+                        },
+                        condition.location,
+                    );
+                }
+                block.append(self.pop_block());
+                self.warnings.truncate(warnings);
+                self.context.add_instruction(
+                    Instruction::While {
+                        cond: Box::new(cond),
+                        block: Box::new(block),
+                    },
+                    expr.location,
+                );
+                Symbol::temp_symbol(&mut self.context, expr.location)
+            }
+            ExpressionType::UncheckedExpression { block } => {
+                let checked = std::mem::replace(&mut self.checked, false);
+                let block = self.compile_block(block);
+                self.checked = checked;
+                let block = block?;
+                self.context.add_instruction(
+                    Instruction::Unchecked {
+                        block: Box::new(block),
+                    },
+                    expr.location,
+                );
+                Symbol::temp_symbol(&mut self.context, expr.location)
+            }
+            ExpressionType::ParenExpression { expression } => {
+                if expression.node.is_atomic() {
+                    self.warnings.push(CompileWarning {
+                        message: String::from("Redundant parentheses"),
+                        location: expr.location,
+                    });
+                }
+                self.compile_expr(expression)
+            }
+            ExpressionType::Literal { value } => {
+                if self.context.current_contract.is_some() {
+                    self.current_contract(expr.location)?.intern(value);
+                }
+                Ok(Symbol::literal_symbol(value.to_string()))
+            }
+            ExpressionType::HexLiteral { value } => Ok(Symbol::bytes_symbol(value.to_string())),
+            ExpressionType::Bool { value } => Ok(Symbol::bool_symbol(*value)),
+            ExpressionType::ArrayLiteral { elements } => {
+                let mut symbols = vec![];
+                let mut typ = Type::Never;
+                for element in elements {
+                    let symbol = self.compile_expr(element)?;
+                    typ = typ.join(&symbol.typ).ok_or_else(|| CompileError {
+                        error: CompileErrorType::TypeError(format!(
+                            "Array elements have different types: {:?} and {:?}",
+                            typ, symbol.typ
+                        )),
+                        location: element.location,
+                    })?;
+                    symbols.push(symbol);
+                }
+                if symbols.is_empty() {
+                    return Err(CompileError {
+                        error: CompileErrorType::TypeError(String::from(
+                            "Cannot infer the element type of an empty array",
+                        )),
+                        location: expr.location,
+                    });
+                }
+                let res = Symbol::temp_symbol(&mut self.context, expr.location)?
+                    .with_type(Type::Array(Box::new(typ), symbols.len()));
+                self.context.add_instruction(
+                    Instruction::Array {
+                        dst: Box::new(res.clone()),
+                        elements: symbols,
+                    },
+                    expr.location,
+                );
+                Ok(res)
+            }
+            ExpressionType::Number { value } => {
+                if value.bits() > 256 {
+                    return Err(CompileError {
+                        error: CompileErrorType::ArithmeticError(String::from(
+                            "Number literal does not fit in uint",
+                        )),
+                        location: expr.location,
+                    });
+                }
+                Ok(Symbol::number(value))
+            }
+            ExpressionType::Identifier { value } => {
+                let symbol = self.get_symbol(value, expr.location)?;
+                self.context.mark_used(value);
+                Ok(symbol)
             }
-            ExpressionType::Literal { value } => Ok(Symbol::literal_symbol(value.to_string())),
-            ExpressionType::Number { value } => Ok(Symbol {
-                id: value.to_string(),
-                num: 0,
-                typ: Type::Uint,
-            }),
-            ExpressionType::Identifier { value } => Ok(self.context.get_symbol(value)),
             _ => Err(CompileError {
                 error: CompileErrorType::SyntaxError(String::from("Unreachable")),
                 location: expr.location,
@@ -218,12 +635,64 @@ impl Compiler {
         }
     }
 
-    fn compile_param(&mut self, ast: &ast::Expression) -> CompileResult<Vec<Symbol>> {
+    /// Emit `operator` applied to `a` and `b`, returning the result temp.
+    fn compile_binary(
+        &mut self,
+        operator: &ast::Operator,
+        a: Symbol,
+        b: Symbol,
+        location: Location,
+    ) -> Result<Symbol> {
+        if operator.is_comparison() && a == b {
+            let always = matches!(
+                operator,
+                ast::Operator::Eq | ast::Operator::Le | ast::Operator::Ge
+            );
+            self.warnings.push(CompileWarning {
+                message: format!("Comparison of `{}` with itself is always {}", a.id, always),
+                location,
+            });
+        }
+        if matches!(operator, ast::Operator::Div | ast::Operator::Mod)
+            && b.value == Some(BigUint::from(0u32))
+        {
+            return Err(CompileError {
+                error: CompileErrorType::ArithmeticError(String::from("Division by zero")),
+                location,
+            });
+        }
+        let dst =
+            Symbol::result_symbol(&mut self.context, operator, a.clone(), b.clone(), location)?;
+        let mut res = Instruction::get_instruction_from_bin_op(
+            operator.clone(),
+            dst.clone(),
+            a,
+            b,
+            location,
+        )?;
+        if !self.checked {
+            res = res.into_wrapping();
+        }
+        self.context.add_instruction(res, location);
+        Ok(dst)
+    }
+
+    fn compile_param(&mut self, ast: &ast::Expression) -> Result<Vec<Symbol>> {
         match &ast.node {
             ExpressionType::Parameters { parameters } => {
-                let mut params = vec![];
+                let mut params: Vec<Symbol> = vec![];
                 for parameter in parameters {
-                    params.push(self.compile_stmt(parameter)?);
+                    let param = self.compile_stmt(parameter)?;
+                    if params.iter().any(|prev| prev.id == param.id) {
+                        return Err(CompileError {
+                            error: CompileErrorType::NameError(format!(
+                                "Duplicate parameter `{}`",
+                                param.id
+                            )),
+                            location: parameter.location,
+                        });
+                    }
+                    params.push(param);
                 }
                 Ok(params)
             }
@@ -241,9 +710,46 @@ impl Compiler {
         }
     }
 
-    fn compile_range(&self, expr: &ast::Expression) -> CompileResult<(BigUint, BigUint)> {
-        if let ast::ExpressionType::Range { start, end } = &expr.node {
-            Ok((start.clone(), end.clone()))
+    fn compile_range(&mut self, expr: &ast::Expression) -> Result<(Symbol, Symbol, Symbol)> {
+        if let ast::ExpressionType::Range { start, end, step } = &expr.node {
+            let start_symbol = self.compile_expr(start)?;
+            if start_symbol.typ != Type::Uint {
+                return Err(CompileError {
+                    error: CompileErrorType::TypeError(String::from("Range start must be uint")),
+                    location: start.location,
+                });
+            }
+            let end_symbol = self.compile_expr(end)?;
+            if end_symbol.typ != Type::Uint {
+                return Err(CompileError {
+                    error: CompileErrorType::TypeError(String::from("Range end must be uint")),
+                    location: end.location,
+                });
+            }
+            let step_symbol = match step {
+                Some(step) => {
+                    let step_symbol = self.compile_expr(step)?;
+                    if step_symbol.typ != Type::Uint {
+                        return Err(CompileError {
+                            error: CompileErrorType::TypeError(String::from(
+                                "Range step must be uint",
+                            )),
+                            location: step.location,
+                        });
+                    }
+                    if step_symbol.value == Some(BigUint::from(0u32)) {
+                        return Err(CompileError {
+                            error: CompileErrorType::ArithmeticError(String::from(
+                                "Range step must not be zero",
+                            )),
+                            location: step.location,
+                        });
+                    }
+                    step_symbol
+                }
+                None => Symbol::number(&BigUint::from(1u32)),
+            };
+            Ok((start_symbol, end_symbol, step_symbol))
         } else {
             Err(CompileError {
                 error: CompileErrorType::SyntaxError(String::from("Range Compile Error")),
@@ -252,7 +758,14 @@ impl Compiler {
         }
     }
 
-    fn compile_block(&mut self, expr: &ast::Expression) -> CompileResult<Block> {
+    fn compile_block(&mut self, expr: &ast::Expression) -> Result<Block> {
+        Ok(self.compile_block_with_value(expr)?.0)
+    }
+
+    fn compile_block_with_value(
+        &mut self,
+        expr: &ast::Expression,
+    ) -> Result<(Block, Option<Symbol>)> {
         if let ast::ExpressionType::CompoundExpression {
             statements,
             return_value,
@@ -262,10 +775,12 @@ impl Compiler {
             for statement in statements {
                 self.compile_stmt(statement)?;
             }
-            if let Some(returns) = return_value {
-                self.compile_expr(returns)?;
-            }
-            Ok(self.context.pop_block())
+            let value = match return_value {
+                Some(returns) => Some(self.compile_expr(returns)?),
+                None => None,
+            };
+            self.dump_symbols();
+            Ok((self.pop_block(), value))
         } else {
             Err(CompileError {
                 error: CompileErrorType::SyntaxError(String::from("Block Compile Error")),
@@ -274,19 +789,411 @@ impl Compiler {
         }
     }
 
+    fn warn_assign_condition(&mut self, condition: &ast::Expression) {
+        if let ExpressionType::AssignExpression { .. } = condition.node {
+            self.warnings.push(CompileWarning {
+                message: String::from("Assignment used as a condition, did you mean `==`?"),
+                location: condition.location,
+            });
+        }
+    }
+
+    /// Compile an `else` branch, which is either a block or a chained `if`.
+    fn compile_else_branch(&mut self, expr: &ast::Expression) -> Result<(Block, Option<Symbol>)> {
+        if let ast::ExpressionType::IfExpression { .. } = expr.node {
+            self.context.add_block();
+            let value = self.compile_expr(expr)?;
+            // An `if` without a value leaves its result untyped.
+            let value = Some(value).filter(|value| value.typ != Type::None);
+            Ok((self.pop_block(), value))
+        } else {
+            self.compile_block_with_value(expr)
+        }
+    }
+
+    /// Close the innermost scope, warning about locals that were never read.
+    fn pop_block(&mut self) -> Block {
+        if let Some(table) = self.context.tables.last() {
+            for (name, location) in &table.unused {
+                self.warnings.push(CompileWarning {
+                    message: format!("Unused variable `{}`", name),
+                    location: *location,
+                });
+            }
+        }
+        self.context.pop_block()
+    }
+
+    fn dump_symbols(&mut self) {
+        if let Some(dump) = &mut self.symbol_dump {
+            if let Some(contract_name) = &self.context.current_contract {
+                let contract = &self.contracts[contract_name];
+                dump.push_str(&format!("contract {}:\n", contract_name));
+                for (name, member) in &contract.member {
+                    dump.push_str(&format!("  {}: {:?}\n", name, member.typ));
+                }
+            }
+            if let Some(function_name) = &self.context.current_function {
+                dump.push_str(&format!("function {}:\n", function_name));
+            }
+            dump.push_str(&self.context.dump_symbols());
+        }
+    }
+
+    fn compile_name(&self, expr: &ast::Expression) -> Result<String> {
+        expr.node.identifier_name().ok_or_else(|| CompileError {
+            error: CompileErrorType::SyntaxError(String::from("Expected identifier")),
+            location: expr.location,
+        })
+    }
+
+    /// Resolve a called function, typed by its declared return type.
+    /// Contract functions take precedence over free functions, and an overloaded
+    /// name resolves to the single overload accepting the argument types.
+    fn compile_function_name(&self, expr: &ast::Expression, args: &[Symbol]) -> Result<Symbol> {
+        let name = self.compile_name(expr)?;
+        let mut scopes = vec![];
+        if let Some(contract_name) = &self.context.current_contract {
+            scopes.push(&self.contracts[contract_name].functions);
+        }
+        scopes.push(&self.free_functions);
+        for functions in scopes {
+            if let Some(resolved) = resolve_overload(functions, &name, args, expr.location) {
+                let (key, function) = resolved?;
+                return Ok(Symbol::named(key.to_string(), function.ret.clone()));
+            }
+        }
+        Err(CompileError {
+            error: CompileErrorType::NameError(format!("Undefined function `{}`", name)),
+            location: expr.location,
+        })
+    }
+
+    /// Resolve a method on the receiver `args[0]` through the libraries attached with `using`.
+    /// The receiver is passed as the first argument, and the callee is named `Lib.method`.
+    fn compile_method_name(&self, expr: &ast::Expression, args: &[Symbol]) -> Result<Symbol> {
+        let name = self.compile_name(expr)?;
+        let typ = &args[0].typ;
+        if let Some(contract_name) = &self.context.current_contract {
+            for (library, attached) in &self.contracts[contract_name].using {
+                if attached != typ {
+                    continue;
+                }
+                let functions = match self.contracts.get(library) {
+                    Some(library) => &library.functions,
+                    None => continue,
+                };
+                if let Some(resolved) = resolve_overload(functions, &name, args, expr.location) {
+                    let (key, function) = resolved?;
+                    return Ok(Symbol::named(
+                        format!("{}.{}", library, key),
+                        function.ret.clone(),
+                    ));
+                }
+            }
+        }
+        Err(CompileError {
+            error: CompileErrorType::NameError(format!(
+                "No function `{}` attached to {:?}",
+                name, typ
+            )),
+            location: expr.location,
+        })
+    }
+
+    /// Resolve a name in the local scopes first, then in the current contract's members.
+    fn get_symbol(&self, name: &str, loc: Location) -> Result<Symbol> {
+        if let Some(symbol) = self.context.find_symbol(name) {
+            return Ok(symbol);
+        }
+        if let Some(contract_name) = &self.context.current_contract {
+            if let Some(member) = self.contracts[contract_name].member.get(name) {
+                return Ok(member.clone());
+            }
+        }
+        Err(CompileError {
+            error: CompileErrorType::NameError(format!("Undefined variable `{}`", name)),
+            location: loc,
+        })
+    }
+
+    fn check_assign_type(&self, dst: &Symbol, src: &Symbol, loc: Location) -> Result<()> {
+        if src.typ == dst.typ || src.typ == Type::Undefined || src.typ == Type::Never {
+            Ok(())
+        } else {
+            Err(CompileError {
+                error: CompileErrorType::TypeError(format!(
+                    "Expected {:?} but got {:?}",
+                    dst.typ, src.typ
+                )),
+                location: loc,
+            })
+        }
+    }
+
+    /// Constants may only be written by their initializer, immutables only by the constructor.
+    fn check_mutable(&self, dst: &Symbol, loc: Location) -> Result<()> {
+        let message = match dst.mutability {
+            ast::Mutability::Constant => format!("Cannot assign to constant `{}`", dst.id),
+            ast::Mutability::Immutable if self.context.current_function.is_some() => format!(
+                "Cannot assign to immutable `{}` outside the constructor",
+                dst.id
+            ),
+            _ => return Ok(()),
+        };
+        Err(CompileError {
+            error: CompileErrorType::TypeError(message),
+            location: loc,
+        })
+    }
+
+    /// Whether the expression can be folded at compile time.
+    fn is_constant_expr(&self, expr: &ast::Expression) -> bool {
+        match &expr.node {
+            ExpressionType::Number { .. }
+            | ExpressionType::Bool { .. }
+            | ExpressionType::Literal { .. }
+            | ExpressionType::HexLiteral { .. } => true,
+            ExpressionType::BinaryExpression { left, right, .. } => {
+                self.is_constant_expr(left) && self.is_constant_expr(right)
+            }
+            ExpressionType::ParenExpression { expression } => self.is_constant_expr(expression),
+            ExpressionType::UnaryExpression { operand, .. } => self.is_constant_expr(operand),
+            ExpressionType::Identifier { value } => self
+                .get_symbol(value, expr.location)
+                .is_ok_and(|symbol| symbol.mutability == ast::Mutability::Constant),
+            _ => false,
+        }
+    }
+
+    /// Declare every member and function up front so they can be used before their definition.
+    fn declare_members(&mut self, members: &ast::Statement) -> Result<()> {
+        if let StatementType::MemberStatement { statements } = &members.node {
+            let contract = self.current_contract(members.location)?;
+            declare_functions(&mut contract.functions, statements)?;
+            for statement in statements {
+                if let StatementType::UsingStatement { library, typ } = &statement.node {
+                    if let Some(library) = library.node.identifier_name() {
+                        contract.using.push((library, Type::get_type(typ)));
+                    }
+                }
+                if let StatementType::InitializerStatement {
+                    variable_type,
+                    mutability,
+                    visibility,
+                    variable,
+                    ..
+                } = &statement.node
+                {
+                    if let Some(name) = variable.node.identifier_name() {
+                        let symbol = Symbol {
+                            mutability: mutability.clone(),
+                            ..Symbol::named(name.clone(), Type::get_type(variable_type))
+                        };
+                        if *visibility == ast::Visibility::Public {
+                            if contract
+                                .functions
+                                .values()
+                                .any(|function| function.name == name)
+                            {
+                                return Err(CompileError {
+                                    error: CompileErrorType::NameError(format!(
+                                        "Getter of public member `{}` conflicts with a function",
+                                        name
+                                    )),
+                                    location: statement.location,
+                                });
+                            }
+                            let mut getter = Function::new(
+                                name.clone(),
+                                vec![],
+                                symbol.typ.clone(),
+                                Block::new(),
+                            );
+                            getter.result = Some(symbol.clone());
+                            contract.functions.insert(name.clone(), getter);
+                        }
+                        contract.member.insert(name, symbol);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Declare every top-level contract up front so a library can be used before its definition.
+    fn declare_contracts(&mut self, stmts: &[ast::Statement]) -> Result<()> {
+        for stmt in stmts {
+            if let StatementType::ContractStatement {
+                contract_name,
+                members,
+            } = &stmt.node
+            {
+                let name = self.compile_name(contract_name)?;
+                self.look_contract(&name);
+                self.declare_members(members)?;
+            }
+        }
+        self.context.current_contract = None;
+        Ok(())
+    }
+
+    /// Declare every free function up front so contracts can call them before their definition.
+    fn declare_free_functions(&mut self, stmts: &[ast::Statement]) -> Result<()> {
+        declare_functions(&mut self.free_functions, stmts)
+    }
+
     fn look_contract(&mut self, name: &str) {
-        self.contracts.insert(name.to_string(), Contract::new());
+        self.contracts
+            .insert(name.to_string(), Contract::new(name.to_string()));
         self.context.current_contract = Some(name.to_string());
     }
 
-    fn add_function(&mut self, name: &str, params: Vec<Symbol>, ret: Type, block: Block) {
-        if let Some(contract_name) = self.context.current_contract.clone() {
-            self.context.current_function = Some(name.to_string());
-            self.contracts
-                .get_mut(&contract_name)
-                .unwrap()
-                .functions
-                .insert(name.to_string(), Function::new(params, ret, block));
+    fn add_function(
+        &mut self,
+        name: &str,
+        params: Vec<Symbol>,
+        ret: Type,
+        block: Block,
+        result: Option<Symbol>,
+        location: Location,
+    ) -> Result<()> {
+        let mut function = Function::new(name.to_string(), params, ret, block);
+        function.result = result;
+        let functions = if self.context.current_contract.is_some() {
+            &mut self.current_contract(location)?.functions
+        } else {
+            &mut self.free_functions
+        };
+        let signature = function.signature();
+        let key = if functions.contains_key(&signature) {
+            signature
+        } else {
+            name.to_string()
+        };
+        functions.insert(key, function);
+        Ok(())
+    }
+
+    /// The contract being compiled.
+    fn current_contract(&mut self, location: Location) -> Result<&mut Contract> {
+        let name = self
+            .context
+            .current_contract
+            .as_ref()
+            .ok_or_else(|| CompileError {
+                error: CompileErrorType::SyntaxError(String::from("Expected to be in a contract")),
+                location,
+            })?;
+        self.contracts.get_mut(name).ok_or_else(|| CompileError {
+            error: CompileErrorType::NameError(format!("Undefined contract `{}`", name)),
+            location,
+        })
+    }
+}
+
+/// Pick the function named `name` among `functions` that accepts `args`,
+/// or `None` when no function has that name.
+fn resolve_overload<'a>(
+    functions: &'a IndexMap<String, Function>,
+    name: &str,
+    args: &[Symbol],
+    location: Location,
+) -> Option<Result<(&'a String, &'a Function)>> {
+    let candidates: Vec<(&String, &Function)> = functions
+        .iter()
+        .filter(|(_, function)| function.name == name)
+        .collect();
+    if candidates.is_empty() {
+        return None;
+    }
+    let matches: Vec<&(&String, &Function)> = candidates
+        .iter()
+        .filter(|(_, function)| function.accepts(args))
+        .collect();
+    let message = match matches.as_slice() {
+        [resolved] => return Some(Ok(**resolved)),
+        [] if candidates.len() == 1 => {
+            let types: Vec<String> = args.iter().map(|arg| arg.typ.to_string()).collect();
+            format!(
+                "`{}` cannot be called with ({})",
+                candidates[0].1.signature(),
+                types.join(",")
+            )
+        }
+        [] => format!("No overload of `{}` matches the arguments", name),
+        _ => format!("Ambiguous call to overloaded `{}`", name),
+    };
+    Some(Err(CompileError {
+        error: CompileErrorType::TypeError(message),
+        location,
+    }))
+}
+
+/// Declare the functions among `stmts` with their parameter and return types.
+/// An overloaded name is keyed by each overload's signature, any other by its name.
+fn declare_functions(
+    functions: &mut IndexMap<String, Function>,
+    stmts: &[ast::Statement],
+) -> Result<()> {
+    let mut declared = vec![];
+    for stmt in stmts {
+        if let StatementType::FunctionStatement {
+            function_name,
+            parameters,
+            returns,
+            ..
+        } = &stmt.node
+        {
+            if let Some(name) = function_name.node.identifier_name() {
+                let ret = returns.as_ref().map_or(Type::None, Type::get_type);
+                let params = declared_params(parameters);
+                declared.push((
+                    Function::new(name, params, ret, Block::new()),
+                    stmt.location,
+                ));
+            }
+        }
+    }
+    let mut signatures = HashSet::new();
+    for (function, location) in &declared {
+        if !signatures.insert(function.signature()) {
+            return Err(CompileError {
+                error: CompileErrorType::NameError(format!(
+                    "Function `{}` is declared more than once",
+                    function.signature()
+                )),
+                location: *location,
+            });
+        }
+    }
+    let names: Vec<String> = declared.iter().map(|(f, _)| f.name.clone()).collect();
+    for (function, _) in declared {
+        let key = if names.iter().filter(|name| **name == function.name).count() > 1 {
+            function.signature()
+        } else {
+            function.name.clone()
+        };
+        functions.insert(key, function);
+    }
+    Ok(())
+}
+
+fn declared_params(parameters: &ast::Expression) -> Vec<Symbol> {
+    let mut params = vec![];
+    if let ExpressionType::Parameters { parameters } = &parameters.node {
+        for parameter in parameters {
+            if let StatementType::InitializerStatement {
+                variable_type,
+                variable,
+                ..
+            } = &parameter.node
+            {
+                if let Some(name) = variable.node.identifier_name() {
+                    params.push(Symbol::named(name, Type::get_type(variable_type)));
+                }
+            }
         }
     }
+    params
 }
@@ -1,3 +1,4 @@
+use std::fmt;
 use ziraffe_parser::location::Location;
 
 #[derive(Debug, PartialEq)]
@@ -9,5 +10,34 @@ pub struct CompileError {
 #[derive(Debug, PartialEq)]
 pub enum CompileErrorType {
     SyntaxError(String),
+    NameError(String),
     TypeError(String),
+    ArithmeticError(String),
+    LimitError(String),
+}
+
+/// A suspicious construct that still compiles.
+#[derive(Debug, PartialEq)]
+pub struct CompileWarning {
+    pub message: String,
+    pub location: Location,
+}
+
+/// Informational output that needs no action.
+#[derive(Debug, PartialEq)]
+pub struct CompileNote {
+    pub message: String,
+    pub location: Location,
+}
+
+/// Contracts that depend on each other, listed along the cycle.
+#[derive(Debug, PartialEq)]
+pub struct CycleError {
+    pub contracts: Vec<String>,
+}
+
+impl fmt::Display for CycleError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Dependency cycle: {}", self.contracts.join(" -> "))
+    }
 }
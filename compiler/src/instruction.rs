@@ -1,12 +1,12 @@
 use crate::error::{CompileError, CompileErrorType};
 use crate::symbol_table::Symbol;
-use num_bigint::BigUint;
+use std::fmt;
 use ziraffe_parser::ast::Operator;
 use ziraffe_parser::location::Location;
 
 type InstructionResult<T> = Result<T, CompileError>;
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
 pub enum Instruction {
     // Bypass
     Add {
@@ -29,15 +29,49 @@ pub enum Instruction {
         left: Box<Symbol>,
         right: Box<Symbol>,
     },
+    Mod {
+        dst: Box<Symbol>,
+        left: Box<Symbol>,
+        right: Box<Symbol>,
+    },
     Pow {
         dst: Box<Symbol>,
         left: Box<Symbol>,
         right: Box<Symbol>,
     },
+    // Arithmetic of an `unchecked` block, wrapping instead of failing on overflow.
+    WrappingAdd {
+        dst: Box<Symbol>,
+        left: Box<Symbol>,
+        right: Box<Symbol>,
+    },
+    WrappingSub {
+        dst: Box<Symbol>,
+        left: Box<Symbol>,
+        right: Box<Symbol>,
+    },
+    WrappingMul {
+        dst: Box<Symbol>,
+        left: Box<Symbol>,
+        right: Box<Symbol>,
+    },
+    WrappingPow {
+        dst: Box<Symbol>,
+        left: Box<Symbol>,
+        right: Box<Symbol>,
+    },
     Assign {
         dst: Box<Symbol>,
         src: Box<Symbol>,
     },
+    Neg {
+        dst: Box<Symbol>,
+        src: Box<Symbol>,
+    },
+    Not {
+        dst: Box<Symbol>,
+        src: Box<Symbol>,
+    },
     And {
         dst: Box<Symbol>,
         left: Box<Symbol>,
@@ -88,15 +122,21 @@ pub enum Instruction {
 
     If {
         cond: Box<Symbol>,
-        block: Box<Block>,
+        then_block: Box<Block>,
+        else_block: Option<Box<Block>>,
     },
     For {
         iter: Box<Symbol>,
-        start: BigUint,
-        end: BigUint,
+        start: Box<Symbol>,
+        end: Box<Symbol>,
+        step: Box<Symbol>,
         block: Box<Block>,
     },
-    Else {
+    Return {
+        value: Option<Box<Symbol>>,
+    },
+    // Runs `block` while `cond` holds; the block re-evaluates `cond` at its end.
+    While {
         cond: Box<Symbol>,
         block: Box<Block>,
     },
@@ -105,9 +145,190 @@ pub enum Instruction {
         func: Box<Symbol>,
         args: Vec<Symbol>,
     },
+    Array {
+        dst: Box<Symbol>,
+        elements: Vec<Symbol>,
+    },
+    // Scope of an `unchecked` block, whose arithmetic uses the wrapping instructions.
+    Unchecked {
+        block: Box<Block>,
+    },
 }
 
 impl Instruction {
+    /// The symbol written by the instruction.
+    pub fn dst(&self) -> Option<&Symbol> {
+        match self {
+            Instruction::Add { dst, .. }
+            | Instruction::Sub { dst, .. }
+            | Instruction::Mul { dst, .. }
+            | Instruction::Div { dst, .. }
+            | Instruction::Mod { dst, .. }
+            | Instruction::Pow { dst, .. }
+            | Instruction::WrappingAdd { dst, .. }
+            | Instruction::WrappingSub { dst, .. }
+            | Instruction::WrappingMul { dst, .. }
+            | Instruction::WrappingPow { dst, .. }
+            | Instruction::Assign { dst, .. }
+            | Instruction::Neg { dst, .. }
+            | Instruction::Not { dst, .. }
+            | Instruction::And { dst, .. }
+            | Instruction::Or { dst, .. }
+            | Instruction::Lt { dst, .. }
+            | Instruction::Le { dst, .. }
+            | Instruction::Gt { dst, .. }
+            | Instruction::Ge { dst, .. }
+            | Instruction::Eq { dst, .. }
+            | Instruction::NotEq { dst, .. }
+            | Instruction::Call { dst, .. }
+            | Instruction::Array { dst, .. } => Some(dst),
+            Instruction::Init { name } | Instruction::InitAssign { name, .. } => Some(name),
+            Instruction::For { iter, .. } => Some(iter),
+            Instruction::If { .. }
+            | Instruction::While { .. }
+            | Instruction::Return { .. }
+            | Instruction::Unchecked { .. } => None,
+        }
+    }
+
+    /// The symbols read by the instruction, not including those of a nested block.
+    pub fn operands(&self) -> Vec<&Symbol> {
+        match self {
+            Instruction::Add { left, right, .. }
+            | Instruction::Sub { left, right, .. }
+            | Instruction::Mul { left, right, .. }
+            | Instruction::Div { left, right, .. }
+            | Instruction::Mod { left, right, .. }
+            | Instruction::Pow { left, right, .. }
+            | Instruction::WrappingAdd { left, right, .. }
+            | Instruction::WrappingSub { left, right, .. }
+            | Instruction::WrappingMul { left, right, .. }
+            | Instruction::WrappingPow { left, right, .. }
+            | Instruction::And { left, right, .. }
+            | Instruction::Or { left, right, .. }
+            | Instruction::Lt { left, right, .. }
+            | Instruction::Le { left, right, .. }
+            | Instruction::Gt { left, right, .. }
+            | Instruction::Ge { left, right, .. }
+            | Instruction::Eq { left, right, .. }
+            | Instruction::NotEq { left, right, .. } => vec![left, right],
+            Instruction::Assign { src, .. }
+            | Instruction::InitAssign { src, .. }
+            | Instruction::Neg { src, .. }
+            | Instruction::Not { src, .. } => vec![src],
+            Instruction::Init { .. } | Instruction::Unchecked { .. } => vec![],
+            Instruction::If { cond, .. } | Instruction::While { cond, .. } => vec![cond],
+            Instruction::For {
+                start, end, step, ..
+            } => vec![start, end, step],
+            Instruction::Call { args, .. } | Instruction::Array { elements: args, .. } => {
+                args.iter().collect()
+            }
+            Instruction::Return { value } => value.iter().map(|value| &**value).collect(),
+        }
+    }
+
+    /// Mutable access to the symbol written by the instruction.
+    pub fn dst_mut(&mut self) -> Option<&mut Symbol> {
+        match self {
+            Instruction::Add { dst, .. }
+            | Instruction::Sub { dst, .. }
+            | Instruction::Mul { dst, .. }
+            | Instruction::Div { dst, .. }
+            | Instruction::Mod { dst, .. }
+            | Instruction::Pow { dst, .. }
+            | Instruction::WrappingAdd { dst, .. }
+            | Instruction::WrappingSub { dst, .. }
+            | Instruction::WrappingMul { dst, .. }
+            | Instruction::WrappingPow { dst, .. }
+            | Instruction::Assign { dst, .. }
+            | Instruction::Neg { dst, .. }
+            | Instruction::Not { dst, .. }
+            | Instruction::And { dst, .. }
+            | Instruction::Or { dst, .. }
+            | Instruction::Lt { dst, .. }
+            | Instruction::Le { dst, .. }
+            | Instruction::Gt { dst, .. }
+            | Instruction::Ge { dst, .. }
+            | Instruction::Eq { dst, .. }
+            | Instruction::NotEq { dst, .. }
+            | Instruction::Call { dst, .. }
+            | Instruction::Array { dst, .. } => Some(dst),
+            Instruction::Init { name } | Instruction::InitAssign { name, .. } => Some(name),
+            Instruction::For { iter, .. } => Some(iter),
+            Instruction::If { .. }
+            | Instruction::While { .. }
+            | Instruction::Return { .. }
+            | Instruction::Unchecked { .. } => None,
+        }
+    }
+
+    /// Mutable access to the symbols read by the instruction, not including those of a nested block.
+    pub fn operands_mut(&mut self) -> Vec<&mut Symbol> {
+        match self {
+            Instruction::Add { left, right, .. }
+            | Instruction::Sub { left, right, .. }
+            | Instruction::Mul { left, right, .. }
+            | Instruction::Div { left, right, .. }
+            | Instruction::Mod { left, right, .. }
+            | Instruction::Pow { left, right, .. }
+            | Instruction::WrappingAdd { left, right, .. }
+            | Instruction::WrappingSub { left, right, .. }
+            | Instruction::WrappingMul { left, right, .. }
+            | Instruction::WrappingPow { left, right, .. }
+            | Instruction::And { left, right, .. }
+            | Instruction::Or { left, right, .. }
+            | Instruction::Lt { left, right, .. }
+            | Instruction::Le { left, right, .. }
+            | Instruction::Gt { left, right, .. }
+            | Instruction::Ge { left, right, .. }
+            | Instruction::Eq { left, right, .. }
+            | Instruction::NotEq { left, right, .. } => vec![left, right],
+            Instruction::Assign { src, .. }
+            | Instruction::InitAssign { src, .. }
+            | Instruction::Neg { src, .. }
+            | Instruction::Not { src, .. } => vec![src],
+            Instruction::Init { .. } | Instruction::Unchecked { .. } => vec![],
+            Instruction::If { cond, .. } | Instruction::While { cond, .. } => vec![cond],
+            Instruction::For {
+                start, end, step, ..
+            } => vec![start, end, step],
+            Instruction::Call { args, .. } | Instruction::Array { elements: args, .. } => {
+                args.iter_mut().collect()
+            }
+            Instruction::Return { value } => value.iter_mut().map(|value| &mut **value).collect(),
+        }
+    }
+
+    /// The nested blocks of a control-flow instruction.
+    pub fn blocks(&self) -> Vec<&Block> {
+        match self {
+            Instruction::If {
+                then_block,
+                else_block,
+                ..
+            } => std::iter::once(then_block)
+                .chain(else_block)
+                .map(|block| &**block)
+                .collect(),
+            Instruction::For { block, .. }
+            | Instruction::While { block, .. }
+            | Instruction::Unchecked { block } => vec![block],
+            _ => vec![],
+        }
+    }
+
+    /// The wrapping form of overflowing arithmetic, other instructions unchanged.
+    pub fn into_wrapping(self) -> Self {
+        match self {
+            Instruction::Add { dst, left, right } => Instruction::WrappingAdd { dst, left, right },
+            Instruction::Sub { dst, left, right } => Instruction::WrappingSub { dst, left, right },
+            Instruction::Mul { dst, left, right } => Instruction::WrappingMul { dst, left, right },
+            Instruction::Pow { dst, left, right } => Instruction::WrappingPow { dst, left, right },
+            instruction => instruction,
+        }
+    }
+
     pub fn get_instruction_from_bin_op(
         op: Operator,
         dst: Symbol,
@@ -136,6 +357,11 @@ impl Instruction {
                 left: Box::new(a),
                 right: Box::new(b),
             }),
+            Operator::Mod => Ok(Instruction::Mod {
+                dst: Box::new(dst),
+                left: Box::new(a),
+                right: Box::new(b),
+            }),
             Operator::Pow => Ok(Instruction::Pow {
                 dst: Box::new(dst),
                 left: Box::new(a),
@@ -181,17 +407,21 @@ impl Instruction {
                 left: Box::new(a),
                 right: Box::new(b),
             }),
-            _ => Err(CompileError {
-                error: CompileErrorType::SyntaxError(String::from("Unreachable")),
+            // The parser only builds `Assign` as an `AssignExpression`.
+            Operator::Assign => Err(CompileError {
+                error: CompileErrorType::SyntaxError(String::from(
+                    "Assignment is not a binary operator",
+                )),
                 location: loc,
             }),
         }
     }
 }
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, PartialEq)]
 pub struct Block {
     codes: Vec<Instruction>,
+    locations: Vec<Location>,
 }
 
 impl Block {
@@ -199,7 +429,144 @@ impl Block {
         Default::default()
     }
 
-    pub fn add_instruction(&mut self, instruction: Instruction) {
+    pub fn add_instruction(&mut self, instruction: Instruction, location: Location) {
         self.codes.push(instruction);
+        self.locations.push(location);
+    }
+
+    pub fn append(&mut self, mut other: Block) {
+        self.codes.append(&mut other.codes);
+        self.locations.append(&mut other.locations);
+    }
+
+    pub fn instructions(&self) -> &[Instruction] {
+        &self.codes
+    }
+
+    pub fn instructions_mut(&mut self) -> &mut [Instruction] {
+        &mut self.codes
+    }
+
+    pub fn locations(&self) -> &[Location] {
+        &self.locations
+    }
+
+    /// Every instruction of the block in order, including those of nested blocks.
+    pub fn all_instructions(&self) -> Vec<&Instruction> {
+        let mut instructions = vec![];
+        for instruction in &self.codes {
+            instructions.push(instruction);
+            for block in instruction.blocks() {
+                instructions.extend(block.all_instructions());
+            }
+        }
+        instructions
+    }
+
+    /// Keep only the instructions for which `f` returns true, with their locations.
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&mut Instruction) -> bool,
+    {
+        let codes = std::mem::take(&mut self.codes);
+        let locations = std::mem::take(&mut self.locations);
+        for (mut instruction, location) in codes.into_iter().zip(locations) {
+            if f(&mut instruction) {
+                self.add_instruction(instruction, location);
+            }
+        }
+    }
+}
+
+impl IntoIterator for Block {
+    type Item = (Instruction, Location);
+    type IntoIter = std::iter::Zip<std::vec::IntoIter<Instruction>, std::vec::IntoIter<Location>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.codes.into_iter().zip(self.locations)
+    }
+}
+
+/// Appends instructions with their locations, so `block.extend(other)` concatenates blocks.
+impl Extend<(Instruction, Location)> for Block {
+    fn extend<I: IntoIterator<Item = (Instruction, Location)>>(&mut self, iter: I) {
+        for (instruction, location) in iter {
+            self.add_instruction(instruction, location);
+        }
+    }
+}
+
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let binary = |f: &mut fmt::Formatter, dst, left, op, right| {
+            write!(f, "{} = {} {} {}", dst, left, op, right)
+        };
+        match self {
+            Instruction::Add { dst, left, right } => binary(f, dst, left, "+", right),
+            Instruction::Sub { dst, left, right } => binary(f, dst, left, "-", right),
+            Instruction::Mul { dst, left, right } => binary(f, dst, left, "*", right),
+            Instruction::Div { dst, left, right } => binary(f, dst, left, "/", right),
+            Instruction::Mod { dst, left, right } => binary(f, dst, left, "%", right),
+            Instruction::Pow { dst, left, right } => binary(f, dst, left, "**", right),
+            Instruction::WrappingAdd { dst, left, right } => binary(f, dst, left, "+%", right),
+            Instruction::WrappingSub { dst, left, right } => binary(f, dst, left, "-%", right),
+            Instruction::WrappingMul { dst, left, right } => binary(f, dst, left, "*%", right),
+            Instruction::WrappingPow { dst, left, right } => binary(f, dst, left, "**%", right),
+            Instruction::And { dst, left, right } => binary(f, dst, left, "&&", right),
+            Instruction::Or { dst, left, right } => binary(f, dst, left, "||", right),
+            Instruction::Lt { dst, left, right } => binary(f, dst, left, "<", right),
+            Instruction::Le { dst, left, right } => binary(f, dst, left, "<=", right),
+            Instruction::Gt { dst, left, right } => binary(f, dst, left, ">", right),
+            Instruction::Ge { dst, left, right } => binary(f, dst, left, ">=", right),
+            Instruction::Eq { dst, left, right } => binary(f, dst, left, "==", right),
+            Instruction::NotEq { dst, left, right } => binary(f, dst, left, "!=", right),
+            Instruction::Assign { dst, src } => write!(f, "{} = {}", dst, src),
+            Instruction::Neg { dst, src } => write!(f, "{} = -{}", dst, src),
+            Instruction::Not { dst, src } => write!(f, "{} = !{}", dst, src),
+            Instruction::Init { name } => write!(f, "init {}", name),
+            Instruction::InitAssign { name, src } => write!(f, "init {} = {}", name, src),
+            Instruction::Call { dst, func, args } => {
+                let args: Vec<String> = args.iter().map(Symbol::to_string).collect();
+                write!(f, "{} = {}({})", dst, func, args.join(", "))
+            }
+            Instruction::Array { dst, elements } => {
+                let elements: Vec<String> = elements.iter().map(Symbol::to_string).collect();
+                write!(f, "{} = [{}]", dst, elements.join(", "))
+            }
+            Instruction::If {
+                cond,
+                then_block,
+                else_block: Some(else_block),
+            } => write!(f, "if {} {} else {}", cond, then_block, else_block),
+            Instruction::If {
+                cond, then_block, ..
+            } => write!(f, "if {} {}", cond, then_block),
+            Instruction::While { cond, block } => write!(f, "while {} {}", cond, block),
+            Instruction::Return { value: Some(value) } => write!(f, "return {}", value),
+            Instruction::Return { value: None } => write!(f, "return"),
+            Instruction::Unchecked { block } => write!(f, "unchecked {}", block),
+            Instruction::For {
+                iter,
+                start,
+                end,
+                step,
+                block,
+            } => write!(
+                f,
+                "for {} in {}..{} step {} {}",
+                iter, start, end, step, block
+            ),
+        }
+    }
+}
+
+impl fmt::Display for Block {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{{")?;
+        for instruction in &self.codes {
+            // Indent nested blocks along with their instruction.
+            writeln!(f, "    {}", instruction.to_string().replace('\n', "\n    "))?;
+        }
+        write!(f, "}}")
     }
 }
@@ -1,7 +1,12 @@
 // I hate this lint too :)
 #![allow(unused_parens)]
 
+pub mod analysis;
+pub mod backend;
 pub mod compiler;
 pub mod error;
 pub mod instruction;
+pub mod optimize;
 pub mod symbol_table;
+#[cfg(any(test, feature = "testutil"))]
+pub mod testutil;
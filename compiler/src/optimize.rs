@@ -0,0 +1,144 @@
+use crate::error::{CompileError, CompileErrorType};
+use crate::instruction::{Block, Instruction};
+use crate::symbol_table::{Function, Symbol, Type};
+use std::collections::HashMap;
+use std::mem::{discriminant, Discriminant};
+
+/// Remove `a = a` assignments, which can be left behind by other passes.
+pub fn eliminate_self_assign(block: &mut Block) {
+    block.retain(|instruction| match instruction {
+        Instruction::Assign { dst, src } => dst != src,
+        Instruction::If {
+            then_block,
+            else_block,
+            ..
+        } => {
+            eliminate_self_assign(then_block);
+            if let Some(else_block) = else_block {
+                eliminate_self_assign(else_block);
+            }
+            true
+        }
+        Instruction::For { block, .. }
+        | Instruction::While { block, .. }
+        | Instruction::Unchecked { block } => {
+            eliminate_self_assign(block);
+            true
+        }
+        _ => true,
+    });
+}
+
+/// Common-subexpression elimination: a binary operation repeated on unchanged operands
+/// within straight-line code becomes an assignment from the earlier result.
+pub fn cse(function: &mut Function) {
+    cse_block(&mut function.codes);
+}
+
+type Expression = (Discriminant<Instruction>, Symbol, Symbol);
+
+fn cse_block(block: &mut Block) {
+    let mut available: HashMap<Expression, Symbol> = HashMap::new();
+    for instruction in block.instructions_mut() {
+        if let Some((dst, left, right)) = binary_operands(instruction) {
+            let key = (discriminant(&*instruction), left.clone(), right.clone());
+            let dst = dst.clone();
+            if let Some(src) = available.get(&key) {
+                *instruction = Instruction::Assign {
+                    dst: Box::new(dst.clone()),
+                    src: Box::new(src.clone()),
+                };
+            } else {
+                available.insert(key, dst.clone());
+            }
+            continue;
+        }
+        match instruction {
+            Instruction::Assign { dst, .. } => invalidate(&mut available, dst),
+            Instruction::Init { name } | Instruction::InitAssign { name, .. } => {
+                invalidate(&mut available, name)
+            }
+            // Nested blocks and calls may write anything, so forget everything.
+            Instruction::If {
+                then_block,
+                else_block,
+                ..
+            } => {
+                cse_block(then_block);
+                if let Some(else_block) = else_block {
+                    cse_block(else_block);
+                }
+                available.clear();
+            }
+            Instruction::For { block, .. }
+            | Instruction::While { block, .. }
+            | Instruction::Unchecked { block } => {
+                cse_block(block);
+                available.clear();
+            }
+            Instruction::Call { .. } => available.clear(),
+            _ => {}
+        }
+    }
+}
+
+fn invalidate(available: &mut HashMap<Expression, Symbol>, written: &Symbol) {
+    available.retain(|(_, left, right), dst| left != written && right != written && dst != written);
+}
+
+fn binary_operands(instruction: &Instruction) -> Option<(&Symbol, &Symbol, &Symbol)> {
+    match instruction {
+        Instruction::Add { dst, left, right }
+        | Instruction::Sub { dst, left, right }
+        | Instruction::Mul { dst, left, right }
+        | Instruction::Div { dst, left, right }
+        | Instruction::Mod { dst, left, right }
+        | Instruction::Pow { dst, left, right }
+        | Instruction::WrappingAdd { dst, left, right }
+        | Instruction::WrappingSub { dst, left, right }
+        | Instruction::WrappingMul { dst, left, right }
+        | Instruction::WrappingPow { dst, left, right }
+        | Instruction::And { dst, left, right }
+        | Instruction::Or { dst, left, right }
+        | Instruction::Lt { dst, left, right }
+        | Instruction::Le { dst, left, right }
+        | Instruction::Gt { dst, left, right }
+        | Instruction::Ge { dst, left, right }
+        | Instruction::Eq { dst, left, right }
+        | Instruction::NotEq { dst, left, right } => Some((dst, left, right)),
+        _ => None,
+    }
+}
+
+/// Re-check assignment and call result types once passes have run.
+/// A mismatch here means a pass substituted a symbol of the wrong type.
+pub fn validate(function: &Function) -> Result<(), CompileError> {
+    validate_block(&function.codes)
+}
+
+fn validate_block(block: &Block) -> Result<(), CompileError> {
+    for (instruction, location) in block.instructions().iter().zip(block.locations()) {
+        let (dst, src) = match instruction {
+            Instruction::Assign { dst, src } | Instruction::InitAssign { name: dst, src } => {
+                (dst, &src.typ)
+            }
+            Instruction::Call { dst, func, .. } => (dst, &func.typ),
+            _ => {
+                for block in instruction.blocks() {
+                    validate_block(block)?;
+                }
+                continue;
+            }
+        };
+        if dst.typ != *src && *src != Type::Undefined && *src != Type::Never {
+            return Err(CompileError {
+                error: CompileErrorType::TypeError(format!(
+                    "Optimized `{}` expects {:?} but got {:?}",
+                    instruction, dst.typ, src
+                )),
+                location: *location,
+            });
+        }
+    }
+    Ok(())
+}
@@ -1,19 +1,27 @@
 use crate::error::{CompileError, CompileErrorType};
 use crate::instruction::{Block, Instruction};
 use indexmap::map::IndexMap;
+use indexmap::set::IndexSet;
+use num_bigint::BigUint;
+use std::fmt;
+use std::hash::{Hash, Hasher};
 use ziraffe_parser::ast;
 use ziraffe_parser::location::Location;
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum Type {
     URL,
     JSON,
     Uint,
     Bool,
     Address,
+    Bytes,
     // only support equality zkp.
     String,
+    Array(Box<Type>, usize),
     None,
+    // Diverging expressions, assignable to any type.
+    Never,
     Undefined,
 }
 
@@ -26,48 +34,209 @@ impl Type {
             ast::Type::Bool => Type::Bool,
             ast::Type::String => Type::String,
             ast::Type::Address => Type::Address,
+            ast::Type::Bytes => Type::Bytes,
+            ast::Type::Array(typ, size) => Type::Array(Box::new(Type::get_type(typ)), *size),
+        }
+    }
+
+    /// Whether values of the type have no fixed size.
+    pub fn is_dynamic(&self) -> bool {
+        match self {
+            Type::Array(typ, _) => typ.is_dynamic(),
+            typ => matches!(typ, Type::URL | Type::JSON | Type::Bytes | Type::String),
+        }
+    }
+
+    /// The type of a value that may come from either `self` or `other`.
+    pub fn join(&self, other: &Type) -> Option<Type> {
+        match (self, other) {
+            (Type::Never, typ) | (typ, Type::Never) => Some(typ.clone()),
+            (a, b) if a == b => Some(a.clone()),
+            _ => None,
+        }
+    }
+
+    pub fn check_operator(&self, op: &ast::Operator) -> Result<(), String> {
+        match (self, op) {
+            (
+                Type::String,
+                ast::Operator::Lt | ast::Operator::Le | ast::Operator::Gt | ast::Operator::Ge,
+            ) => Err(String::from("String only supports equality comparison")),
+            (Type::Address, op) if !op.is_equality() => {
+                Err(String::from("Address only supports equality comparison"))
+            }
+            (Type::Array(..), op) if !op.is_equality() => {
+                Err(String::from("Array only supports equality comparison"))
+            }
+            (Type::Bool, op) if !op.is_equality() && !op.is_logical() => Err(String::from(
+                "Bool does not support arithmetic or ordering, use logical operators `&&` or `||`",
+            )),
+            _ => Ok(()),
         }
     }
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone)]
 pub struct Symbol {
     pub id: String,
     pub num: u32,
     pub typ: Type,
+    // Numeric literals keep their value to avoid re-parsing `id`.
+    pub value: Option<BigUint>,
+    pub mutability: ast::Mutability,
+    // Readable name for temps, only used when printing.
+    pub hint: Option<String>,
+}
+
+// `hint` is left out so printing never changes what a symbol is.
+impl PartialEq for Symbol {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+            && self.num == other.num
+            && self.typ == other.typ
+            && self.value == other.value
+            && self.mutability == other.mutability
+    }
+}
+
+impl Eq for Symbol {}
+
+impl Hash for Symbol {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.id.hash(state);
+        self.num.hash(state);
+        self.typ.hash(state);
+        self.value.hash(state);
+        self.mutability.hash(state);
+    }
+}
+
+impl fmt::Display for Type {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Type::URL => write!(f, "url"),
+            Type::JSON => write!(f, "json"),
+            Type::Uint => write!(f, "uint"),
+            Type::Bool => write!(f, "bool"),
+            Type::Address => write!(f, "address"),
+            Type::Bytes => write!(f, "bytes"),
+            Type::String => write!(f, "string"),
+            Type::Array(typ, size) => write!(f, "{}[{}]", typ, size),
+            Type::None => write!(f, "none"),
+            Type::Never => write!(f, "never"),
+            Type::Undefined => write!(f, "undefined"),
+        }
+    }
+}
+
+impl fmt::Display for Symbol {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if !self.is_temp() {
+            write!(f, "{}", self.id)
+        } else {
+            write!(f, "{}_{}", self.hint.as_deref().unwrap_or(""), self.num)
+        }
+    }
 }
 
 type SymbolResult<T> = Result<T, CompileError>;
 
 impl Symbol {
-    pub fn temp_symbol(context: &mut Context) -> Self {
-        Symbol {
+    /// Temps are the only numbered symbols.
+    pub fn is_temp(&self) -> bool {
+        self.num != 0
+    }
+
+    pub fn temp_symbol(context: &mut Context, loc: Location) -> SymbolResult<Self> {
+        Ok(Symbol {
             id: String::from("_"),
-            num: context.numbering_temp(),
+            num: context.numbering_temp(loc)?,
             typ: Type::None,
-        }
+            value: None,
+            mutability: ast::Mutability::Mutable,
+            hint: None,
+        })
     }
-    pub fn literal_symbol(literal: String) -> Self {
+    pub fn named(id: String, typ: Type) -> Self {
         Symbol {
-            id: literal,
+            id,
             num: 0,
-            typ: Type::String,
+            typ,
+            value: None,
+            mutability: ast::Mutability::Mutable,
+            hint: None,
+        }
+    }
+
+    /// The symbol refined to `typ`; see `set_type`.
+    pub fn with_type(mut self, typ: Type) -> Self {
+        self.set_type(typ);
+        self
+    }
+
+    /// Refine a symbol whose type is not known yet. Changing a concrete type is a
+    /// compiler bug, caught in debug builds.
+    pub fn set_type(&mut self, typ: Type) {
+        debug_assert!(
+            matches!(self.typ, Type::Undefined | Type::None) || self.typ == typ,
+            "cannot change the type of `{}` from {:?} to {:?}",
+            self,
+            self.typ,
+            typ
+        );
+        self.typ = typ;
+    }
+
+    pub fn undefined(name: &str) -> Self {
+        Symbol::named(name.to_string(), Type::Undefined)
+    }
+
+    /// The id is always the decimal value, whatever radix the literal was written in.
+    pub fn number(number: &BigUint) -> Self {
+        Symbol {
+            value: Some(number.clone()),
+            ..Symbol::named(number.to_str_radix(10), Type::Uint)
         }
     }
 
+    pub fn literal_symbol(literal: String) -> Self {
+        Symbol::named(literal, Type::String)
+    }
+
+    pub fn bytes_symbol(hex: String) -> Self {
+        Symbol::named(hex, Type::Bytes)
+    }
+
+    pub fn bool_symbol(value: bool) -> Self {
+        Symbol::named(value.to_string(), Type::Bool)
+    }
+
     pub fn result_symbol(
         context: &mut Context,
+        op: &ast::Operator,
         a: Symbol,
         b: Symbol,
         loc: Location,
     ) -> SymbolResult<Self> {
-        println!("{:#?} a : b {:#?}", a.typ, b.typ);
         if a.typ == b.typ {
-            let typ = b.typ;
+            if let Err(msg) = a.typ.check_operator(op) {
+                return Err(CompileError {
+                    error: CompileErrorType::TypeError(msg),
+                    location: loc,
+                });
+            }
+            let typ = if op.is_comparison() {
+                Type::Bool
+            } else {
+                b.typ
+            };
             Ok(Symbol {
                 id: String::from(""),
-                num: context.numbering_temp(),
+                num: context.numbering_temp(loc)?,
                 typ,
+                value: None,
+                mutability: ast::Mutability::Mutable,
+                hint: Some(format!("{:?}_result", op).to_lowercase()),
             })
         } else {
             Err(CompileError {
@@ -81,6 +250,8 @@ impl Symbol {
 #[derive(Default)]
 pub struct SymbolTable {
     pub symbols: IndexMap<String, Symbol>,
+    // Locals not read yet, with where they were declared.
+    pub unused: IndexMap<String, Location>,
 }
 
 impl SymbolTable {
@@ -91,31 +262,200 @@ impl SymbolTable {
 
 #[derive(Debug)]
 pub struct Function {
-    params: Vec<Symbol>,
-    codes: Block,
-    ret: Type,
+    pub(crate) name: String,
+    pub(crate) params: Vec<Symbol>,
+    pub(crate) codes: Block,
+    pub(crate) ret: Type,
+    // Trailing value of the body, if any.
+    pub(crate) result: Option<Symbol>,
 }
 
 impl Function {
-    pub fn new(params: Vec<Symbol>, ret: Type, codes: Block) -> Self {
-        Function { params, codes, ret }
+    pub fn new(name: String, params: Vec<Symbol>, ret: Type, codes: Block) -> Self {
+        Function {
+            name,
+            params,
+            codes,
+            ret,
+            result: None,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn params(&self) -> &[Symbol] {
+        &self.params
+    }
+
+    pub fn codes(&self) -> &Block {
+        &self.codes
+    }
+
+    pub fn codes_mut(&mut self) -> &mut Block {
+        &mut self.codes
+    }
+
+    pub fn ret(&self) -> &Type {
+        &self.ret
+    }
+
+    pub fn result(&self) -> Option<&Symbol> {
+        self.result.as_ref()
+    }
+
+    pub fn param_names(&self) -> Vec<&str> {
+        self.params.iter().map(|param| param.id.as_str()).collect()
+    }
+
+    /// The name with its parameter types, e.g. `f(uint,address)`; overloads are keyed by it.
+    pub fn signature(&self) -> String {
+        let types: Vec<String> = self
+            .params
+            .iter()
+            .map(|param| param.typ.to_string())
+            .collect();
+        format!("{}({})", self.name, types.join(","))
+    }
+
+    /// Whether arguments of these types can be passed to the function.
+    pub fn accepts(&self, args: &[Symbol]) -> bool {
+        self.params.len() == args.len()
+            && self
+                .params
+                .iter()
+                .zip(args)
+                .all(|(param, arg)| param.typ.join(&arg.typ) == Some(param.typ.clone()))
+    }
+
+    /// Conservatively check that the function neither writes a member
+    /// nor calls anything that might, so recursion counts as impure.
+    pub fn is_pure(&self, contract: &Contract) -> bool {
+        self.check_pure(contract, &mut vec![])
+    }
+
+    fn check_pure<'a>(&'a self, contract: &'a Contract, calls: &mut Vec<&'a str>) -> bool {
+        for instruction in self.codes.all_instructions() {
+            match instruction {
+                Instruction::Assign { dst, .. } if contract.is_member(dst) => return false,
+                Instruction::Call { func, .. } => {
+                    if calls.contains(&func.id.as_str()) {
+                        return false;
+                    }
+                    match contract.functions.get(&func.id) {
+                        Some(callee) => {
+                            calls.push(&func.id);
+                            if !callee.check_pure(contract, calls) {
+                                return false;
+                            }
+                            calls.pop();
+                        }
+                        None => return false,
+                    }
+                }
+                _ => {}
+            }
+        }
+        true
     }
 }
 
 #[derive(Debug, Default)]
 pub struct Contract {
+    pub name: String,
     pub member: IndexMap<String, Symbol>,
     pub functions: IndexMap<String, Function>,
+    // Member initialization run on deploy.
+    pub constructor: Block,
+    // Libraries attached to a type by `using Lib for Type;`, in declaration order.
+    pub using: Vec<(String, Type)>,
+    // String literals used by the contract, each stored once.
+    pub strings: IndexSet<String>,
 }
 
 impl Contract {
-    pub fn new() -> Self {
-        Default::default()
+    pub fn new(name: String) -> Self {
+        Contract {
+            name,
+            ..Default::default()
+        }
+    }
+
+    pub fn is_member(&self, symbol: &Symbol) -> bool {
+        self.member.get(&symbol.id) == Some(symbol)
     }
 
     pub fn add_function(&mut self, name: String, func: Function) {
         self.functions.insert(name, func);
     }
+
+    /// Add a string literal to the pool, returning its index there.
+    pub fn intern(&mut self, literal: &str) -> usize {
+        self.strings.insert_full(literal.to_string()).0
+    }
+
+    /// Index of a string literal in the pool.
+    pub fn string_index(&self, literal: &str) -> Option<usize> {
+        self.strings.get_index_of(literal)
+    }
+
+    /// Human-readable differences from `other`, for snapshot testing.
+    pub fn diff(&self, other: &Contract) -> Vec<String> {
+        let mut diffs = vec![];
+        if self.name != other.name {
+            diffs.push(format!(
+                "renamed contract `{}` to `{}`",
+                self.name, other.name
+            ));
+        }
+        for (name, member) in &self.member {
+            match other.member.get(name) {
+                None => diffs.push(format!("removed member `{}`", name)),
+                Some(changed) if changed != member => diffs.push(format!(
+                    "changed member `{}`: {:?} -> {:?}",
+                    name, member.typ, changed.typ
+                )),
+                _ => {}
+            }
+        }
+        for name in other.member.keys() {
+            if !self.member.contains_key(name) {
+                diffs.push(format!("added member `{}`", name));
+            }
+        }
+        for (name, function) in &self.functions {
+            match other.functions.get(name) {
+                None => diffs.push(format!("removed function `{}`", name)),
+                Some(changed)
+                    if changed.params != function.params
+                        || changed.ret != function.ret
+                        || changed.codes.instructions() != function.codes.instructions() =>
+                {
+                    diffs.push(format!("changed function `{}`", name))
+                }
+                _ => {}
+            }
+        }
+        for name in other.functions.keys() {
+            if !self.functions.contains_key(name) {
+                diffs.push(format!("added function `{}`", name));
+            }
+        }
+        if self.constructor.instructions() != other.constructor.instructions() {
+            diffs.push(String::from("changed constructor"));
+        }
+        diffs
+    }
+
+    /// Every instruction of the constructor and all functions, including nested blocks.
+    pub fn all_instructions(&self) -> impl Iterator<Item = &Instruction> {
+        self.constructor.all_instructions().into_iter().chain(
+            self.functions
+                .values()
+                .flat_map(|function| function.codes.all_instructions()),
+        )
+    }
 }
 
 #[derive(Default)]
@@ -125,6 +465,9 @@ pub struct Context {
     pub current_contract: Option<String>,
     pub current_function: Option<String>,
     pub temp_number: u32,
+    // Cap on temp numbers, `u32::MAX` when unset.
+    pub max_temp: Option<u32>,
+    pub label_number: u32,
     pub is_member: bool,
 }
 
@@ -133,11 +476,16 @@ impl Context {
         Default::default()
     }
 
-    pub fn add_instruction(&mut self, instruction: Instruction) {
+    pub fn add_instruction(&mut self, instruction: Instruction, location: Location) {
         self.current_codes
             .last_mut()
             .unwrap()
-            .add_instruction(instruction);
+            .add_instruction(instruction, location);
+    }
+
+    /// Append the instructions of `block` to the current block.
+    pub fn append_block(&mut self, block: Block) {
+        self.current_codes.last_mut().unwrap().append(block);
     }
 
     pub fn add_block(&mut self) {
@@ -150,21 +498,70 @@ impl Context {
         self.current_codes.pop().unwrap()
     }
 
-    pub fn numbering_temp(&mut self) -> u32 {
+    /// Render every open scope, outermost first, for debugging.
+    pub fn dump_symbols(&self) -> String {
+        let mut dump = String::new();
+        for (depth, table) in self.tables.iter().enumerate() {
+            dump.push_str(&format!("scope {}:\n", depth));
+            for (name, symbol) in &table.symbols {
+                dump.push_str(&format!("  {}: {:?}\n", name, symbol.typ));
+            }
+        }
+        dump
+    }
+
+    pub fn numbering_temp(&mut self, loc: Location) -> SymbolResult<u32> {
+        if self.temp_number >= self.max_temp.unwrap_or(u32::MAX) {
+            return Err(CompileError {
+                error: CompileErrorType::LimitError(String::from(
+                    "Too many temporaries in program",
+                )),
+                location: loc,
+            });
+        }
         self.temp_number += 1;
-        self.temp_number
+        Ok(self.temp_number)
     }
 
-    pub fn get_symbol(&self, name: &str) -> Symbol {
+    /// Number a branch target. Labels are counted apart from temps.
+    pub fn numbering_label(&mut self) -> u32 {
+        self.label_number += 1;
+        self.label_number
+    }
+
+    pub fn find_symbol(&self, name: &str) -> Option<Symbol> {
         for table in self.tables.iter().rev() {
             if let Some(symbol) = table.symbols.get(name) {
-                return symbol.clone();
+                return Some(symbol.clone());
             }
         }
-        Symbol {
-            id: name.to_string(),
-            num: 0,
-            typ: Type::Undefined,
+        None
+    }
+
+    pub fn get_symbol(&self, name: &str) -> Symbol {
+        if let Some(symbol) = self.find_symbol(name) {
+            return symbol;
+        }
+        Symbol::undefined(name)
+    }
+
+    /// Add a local that should be reported if it is never read.
+    pub fn add_local(&mut self, name: &str, sym: Symbol, location: Location) {
+        self.add_symbol(name, sym);
+        self.tables
+            .last_mut()
+            .unwrap()
+            .unused
+            .insert(name.to_string(), location);
+    }
+
+    /// Mark the innermost binding of `name` as read.
+    pub fn mark_used(&mut self, name: &str) {
+        for table in self.tables.iter_mut().rev() {
+            if table.symbols.contains_key(name) {
+                table.unused.shift_remove(name);
+                return;
+            }
         }
     }
 
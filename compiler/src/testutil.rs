@@ -0,0 +1,19 @@
+/// Generate a contract with `functions` functions, each nesting `depth` loops.
+pub fn generate_contract(functions: usize, depth: usize) -> String {
+    let mut source = String::from("contract Generated {\n    uint total;\n");
+    for function in 0..functions {
+        let mut body = String::from("x = x + total;");
+        for level in (0..depth).rev() {
+            body = format!(
+                "for i{} in 0..10 {{ x = x + i{}; {} }};",
+                level, level, body
+            );
+        }
+        source.push_str(&format!(
+            "    function f{}(uint a) returns (uint) {{ uint x = a; {} x }}\n",
+            function, body
+        ));
+    }
+    source.push_str("}\n");
+    source
+}
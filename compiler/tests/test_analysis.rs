@@ -0,0 +1,34 @@
+use indexmap::map::IndexMap;
+use ziraffe_compiler::analysis::topo_order;
+use ziraffe_compiler::error::CycleError;
+use ziraffe_compiler::symbol_table::{Contract, Type};
+
+fn contracts(uses: &[(&str, &[&str])]) -> IndexMap<String, Contract> {
+    let mut contracts = IndexMap::new();
+    for (name, libraries) in uses {
+        let mut contract = Contract::new(name.to_string());
+        for library in libraries.iter() {
+            contract.using.push((library.to_string(), Type::Uint));
+        }
+        contracts.insert(name.to_string(), contract);
+    }
+    contracts
+}
+
+#[test]
+fn test_topo_order() {
+    let order = topo_order(&contracts(&[("B", &["A"]), ("C", &[]), ("A", &[])])).unwrap();
+    assert_eq!(order, vec!["A", "B", "C"]);
+}
+
+#[test]
+fn test_topo_order_cycle() {
+    let err = topo_order(&contracts(&[("A", &["B"]), ("B", &["A"])])).unwrap_err();
+    assert_eq!(
+        err,
+        CycleError {
+            contracts: vec![String::from("A"), String::from("B"), String::from("A")]
+        }
+    );
+    assert_eq!(err.to_string(), "Dependency cycle: A -> B -> A");
+}
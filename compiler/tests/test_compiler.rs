@@ -1,4 +1,14 @@
-use ziraffe_compiler::compiler::compile_program;
+use num_bigint::BigUint;
+use ziraffe_compiler::compiler::{
+    check_expression, compile, compile_program, compile_program_with_symbols, source_map,
+};
+use ziraffe_compiler::error::{CompileErrorType, CompileWarning};
+use ziraffe_compiler::instruction::Instruction;
+use ziraffe_compiler::symbol_table::Type;
+use ziraffe_compiler::testutil::generate_contract;
+use ziraffe_parser::ast::{ExpressionType, Program, Statement, StatementType};
+use ziraffe_parser::error::{LexicalErrorType, ParseErrorType};
+use ziraffe_parser::location::Location;
 use ziraffe_parser::parser;
 
 #[test]
@@ -9,5 +19,1186 @@ fn test_compile_program() {
     assert!(program.is_ok());
     let contracts = compile_program(&program.unwrap()).unwrap();
     let contract = contracts.get("A").unwrap();
+    let _function = contract.functions.get("f").unwrap();
+}
+
+#[test]
+fn test_function_params() {
+    let program =
+        parser::parse_program("contract A { function f() {} function g(uint a) {} }").unwrap();
+    let contracts = compile_program(&program).unwrap();
+    let functions = &contracts["A"].functions;
+    assert!(functions["f"].params().is_empty());
+    assert_eq!(functions["g"].params()[0].typ, Type::Uint);
+}
+
+#[test]
+fn test_named_ir() {
+    let program = parser::parse_program("contract A { function f() {} function g() {} }").unwrap();
+    let mut contracts = compile_program(&program).unwrap();
+    let mut contract = contracts.swap_remove("A").unwrap();
+    assert_eq!(contract.name, "A");
+    let function = contract.functions.swap_remove("g").unwrap();
+    assert_eq!(function.name(), "g");
+}
+
+#[test]
+fn test_source_map() {
+    let source = "contract A { function f() { uint a = 1; a + 2; } }";
+    let program = parser::parse_program(source).unwrap();
+    let contracts = compile_program(&program).unwrap();
+    let map = source_map(contracts.get("A").unwrap());
+    assert_eq!(map.len(), 2);
+    let (function, index, location) = &map[1];
+    assert_eq!(function, "f");
+    assert_eq!(*index, 1);
+    assert_eq!(location.column(), source.find('+').unwrap() + 1);
+
+    let source = "contract A { function f() { uint a = 1; } function g() { uint b = 2; b + 3; } }";
+    let program = parser::parse_program(source).unwrap();
+    let contracts = compile_program(&program).unwrap();
+    let map = source_map(&contracts["A"]);
+    let entries: Vec<(&str, usize, usize)> = map
+        .iter()
+        .map(|(function, index, location)| (function.as_str(), *index, location.column()))
+        .collect();
+    assert_eq!(
+        entries,
+        vec![
+            ("f", 0, source.find("uint a").unwrap() + 1),
+            ("g", 0, source.find("uint b").unwrap() + 1),
+            ("g", 1, source.find('+').unwrap() + 1),
+        ]
+    );
+}
+
+#[test]
+fn test_member_in_function() {
+    let program =
+        parser::parse_program("contract A { uint b; function f() { uint a = b; } }").unwrap();
+    let contracts = compile_program(&program).unwrap();
+    let function = contracts.get("A").unwrap().functions.get("f").unwrap();
+    if let Instruction::InitAssign { src, .. } = &function.codes().instructions()[0] {
+        assert_eq!(src.id, "b");
+        assert_eq!(src.typ, Type::Uint);
+    } else {
+        panic!("expected InitAssign");
+    }
+}
+
+#[test]
+fn test_member_resolution() {
+    let program = parser::parse_program(
+        "contract A { uint b; function f() { b = b + 1; } function g() { c = b < 1; } bool c; }",
+    )
+    .unwrap();
+    let contracts = compile_program(&program).unwrap();
+    let contract = contracts.get("A").unwrap();
+    let f = contract.functions.get("f").unwrap();
+    if let Instruction::Add { left, .. } = &f.codes().instructions()[0] {
+        assert_eq!(left.id, "b");
+        assert_eq!(left.typ, Type::Uint);
+    } else {
+        panic!("expected Add");
+    }
+    let g = contract.functions.get("g").unwrap();
+    if let Instruction::Assign { dst, .. } = &g.codes().instructions()[1] {
+        assert_eq!(dst.id, "c");
+        assert_eq!(dst.typ, Type::Bool);
+    } else {
+        panic!("expected Assign");
+    }
+
+    let program =
+        parser::parse_program("contract A { uint b; function f() { bool b; b = b; } }").unwrap();
+    let contracts = compile_program(&program).unwrap();
+    let f = contracts.get("A").unwrap().functions.get("f").unwrap();
+    if let Instruction::Assign { dst, .. } = &f.codes().instructions()[1] {
+        assert_eq!(dst.typ, Type::Bool);
+    } else {
+        panic!("expected Assign");
+    }
+}
+
+#[test]
+fn test_assign_type() {
+    assert!(compile_function("uint a; uint b = 1; a = b;").is_ok());
+    assert_eq!(
+        compile_function("bool c; uint b = 1; c = b;"),
+        Err(CompileErrorType::TypeError(String::from(
+            "Expected Bool but got Uint"
+        )))
+    );
+}
+
+#[test]
+fn test_hex_literal() {
+    let program =
+        parser::parse_program("contract A { function f() { bytes a = hex\"00ff\"; } }").unwrap();
+    assert!(compile_program(&program).is_ok());
+
+    let err =
+        parser::parse_program("contract A { function f() { bytes a = hex\"0\"; } }").unwrap_err();
+    assert_eq!(
+        err.error,
+        ParseErrorType::Lexical(LexicalErrorType::OtherError(String::from(
+            "Hex literal must have an even number of digits"
+        )))
+    );
+    assert_eq!(err.location, Location::new(1, 39));
+    let err = check_expression("hex\"0\"", Type::Bytes).unwrap_err();
+    assert_eq!(
+        err.error,
+        CompileErrorType::SyntaxError(String::from(
+            "Hex literal must have an even number of digits"
+        ))
+    );
+    assert_eq!(err.location, Location::new(1, 1));
+}
+
+fn compile_source(source: &str) -> Result<(), CompileErrorType> {
+    let program = parser::parse_program(source).unwrap();
+    compile_program(&program)
+        .map(|_| ())
+        .map_err(|err| err.error)
+}
+
+fn compile_function(body: &str) -> Result<(), CompileErrorType> {
+    compile_source(&format!("contract A {{ function f() {{ {} }} }}", body))
+}
+
+#[test]
+fn test_string_comparison() {
+    assert!(compile_function("string a = \"x\"; string b = \"y\"; a == b;").is_ok());
+    assert!(compile_function("string a = \"x\"; string b = \"y\"; a != b;").is_ok());
+    assert_eq!(
+        compile_function("string a = \"x\"; string b = \"y\"; a < b;"),
+        Err(CompileErrorType::TypeError(String::from(
+            "String only supports equality comparison"
+        )))
+    );
+    assert!(compile_function("string a = \"x\"; string b = \"y\"; a >= b;").is_err());
+}
+
+#[test]
+fn test_address_operation() {
+    assert!(compile_function("address addr1; address addr2; addr1 == addr2;").is_ok());
+    assert_eq!(
+        compile_function("address addr1; address addr2; addr1 + addr2;"),
+        Err(CompileErrorType::TypeError(String::from(
+            "Address only supports equality comparison"
+        )))
+    );
+    assert!(compile_function("address addr1; address addr2; addr1 * addr2;").is_err());
+}
+
+#[test]
+fn test_bool_operation() {
+    assert!(compile_function("bool a; bool b; a && b;").is_ok());
+    assert!(compile_function("bool a; bool b; a || b;").is_ok());
+    assert!(compile_function("bool a; bool b; a == b;").is_ok());
+    assert_eq!(
+        compile_function("bool a; bool b; a + b;"),
+        Err(CompileErrorType::TypeError(String::from(
+            "Bool does not support arithmetic or ordering, use logical operators `&&` or `||`"
+        )))
+    );
+    assert!(compile_function("bool a; bool b; a < b;").is_err());
+}
+
+#[test]
+fn test_member_default() {
+    let program = parser::parse_program("contract A { uint b = 10; bool c; }").unwrap();
+    let contracts = compile_program(&program).unwrap();
+    let contract = contracts.get("A").unwrap();
+    assert_eq!(contract.member.get("b").unwrap().typ, Type::Uint);
+    let instructions = contract.constructor.instructions();
+    assert_eq!(instructions.len(), 1);
+    if let Instruction::InitAssign { name, src } = &instructions[0] {
+        assert_eq!(name.id, "b");
+        assert_eq!(src.id, "10");
+    } else {
+        panic!("expected InitAssign");
+    }
+
+    let program = parser::parse_program("contract A { uint b = \"x\"; }").unwrap();
+    assert_eq!(
+        compile_program(&program).unwrap_err().error,
+        CompileErrorType::TypeError(String::from("Expected Uint but got String"))
+    );
+}
+
+#[test]
+fn test_function_parameters() {
+    let program =
+        parser::parse_program("contract A { function f(uint a, bool b) { a = a + 1; } }").unwrap();
+    let contracts = compile_program(&program).unwrap();
+    let contract = contracts.get("A").unwrap();
+    assert!(contract.constructor.instructions().is_empty());
     let function = contract.functions.get("f").unwrap();
+    assert_eq!(function.params().len(), 2);
+    assert_eq!(function.params()[1].typ, Type::Bool);
+}
+
+#[test]
+fn test_initializer_default_type() {
+    assert!(compile_function("uint a = 1; string b = \"x\"; uint c = a;").is_ok());
+    assert_eq!(
+        compile_function("uint a = \"x\";"),
+        Err(CompileErrorType::TypeError(String::from(
+            "Expected Uint but got String"
+        )))
+    );
+    assert!(compile_function("uint a = 1; bool b = a;").is_err());
+}
+
+#[test]
+fn test_all_instructions() {
+    let program = parser::parse_program(
+        "contract A { uint b = 1; function f() { uint a = 0; for i in 0..10 { a = a + 1; } } }",
+    )
+    .unwrap();
+    let contracts = compile_program(&program).unwrap();
+    let contract = contracts.get("A").unwrap();
+    assert_eq!(contract.all_instructions().count(), 5);
+    assert_eq!(
+        contract
+            .all_instructions()
+            .filter(|instruction| matches!(instruction, Instruction::Add { .. }))
+            .count(),
+        1
+    );
+}
+
+#[test]
+fn test_function_is_pure() {
+    let program = parser::parse_program(
+        "contract A { uint b; \
+         function f() { uint a = 1; a = a + 2; } \
+         function g() { b = 1; } \
+         function h() { f(); } \
+         function i() { g(); } }",
+    )
+    .unwrap();
+    let contracts = compile_program(&program).unwrap();
+    let contract = contracts.get("A").unwrap();
+    let is_pure = |name: &str| contract.functions.get(name).unwrap().is_pure(contract);
+    assert!(is_pure("f"));
+    assert!(!is_pure("g"));
+    assert!(is_pure("h"));
+    assert!(!is_pure("i"));
+}
+
+#[test]
+fn test_name_error() {
+    assert_eq!(
+        compile_function("uint a = b;"),
+        Err(CompileErrorType::NameError(String::from(
+            "Undefined variable `b`"
+        )))
+    );
+    assert_eq!(
+        compile_function("g();"),
+        Err(CompileErrorType::NameError(String::from(
+            "Undefined function `g`"
+        )))
+    );
+    assert_eq!(
+        compile_function("for i in 0..10 { i; }; i;"),
+        Err(CompileErrorType::NameError(String::from(
+            "Undefined variable `i`"
+        )))
+    );
+    assert!(compile_function("f();").is_ok());
+}
+
+#[test]
+fn test_arithmetic_error() {
+    assert_eq!(
+        compile_function("uint a = 1; a / 0;"),
+        Err(CompileErrorType::ArithmeticError(String::from(
+            "Division by zero"
+        )))
+    );
+    assert!(compile_function("uint a = 1; a / 1;").is_ok());
+    let too_big = format!("uint a = 1{};", "0".repeat(80));
+    assert_eq!(
+        compile_function(&too_big),
+        Err(CompileErrorType::ArithmeticError(String::from(
+            "Number literal does not fit in uint"
+        )))
+    );
+}
+
+#[test]
+fn test_range_bounds() {
+    let bounds = |body: &str| {
+        let source = format!("contract A {{ function f() {{ {} }} }}", body);
+        let contracts = compile_program(&parser::parse_program(&source).unwrap()).unwrap();
+        let codes = contracts["A"].functions["f"].codes().instructions();
+        match codes
+            .iter()
+            .find(|code| matches!(code, Instruction::For { .. }))
+        {
+            Some(Instruction::For { start, end, .. }) => ((**start).clone(), (**end).clone()),
+            _ => panic!("expected For"),
+        }
+    };
+    let (start, end) = bounds("for i in 0..10 { i; };");
+    assert_eq!(start.value, Some(BigUint::from(0u32)));
+    assert_eq!(end.value, Some(BigUint::from(10u32)));
+
+    let (start, end) = bounds("uint a = 2; uint n = 10; for i in a..n { i; };");
+    assert_eq!((start.id.as_str(), end.id.as_str()), ("a", "n"));
+
+    let (start, end) = bounds("uint a = 2; for i in a + 1..a + 5 { i; };");
+    // Each bound is the result of its own addition.
+    assert_ne!(start, end);
+    assert_eq!((start.value, end.value), (None, None));
+    assert_eq!((start.typ, end.typ), (Type::Uint, Type::Uint));
+}
+
+#[test]
+fn test_range_bound_type() {
+    assert!(compile_function("uint n = 10; for i in 0..n + 1 { i; };").is_ok());
+    let source = "contract A { function f() { bool b; for i in b..10 { i; }; } }";
+    let err = compile_program(&parser::parse_program(source).unwrap()).unwrap_err();
+    assert_eq!(
+        err.error,
+        CompileErrorType::TypeError(String::from("Range start must be uint"))
+    );
+    assert_eq!(err.location.column(), source.find("b..").unwrap() + 1);
+
+    let source = "contract A { function f() { for i in 0..\"x\" { i; }; } }";
+    let err = compile_program(&parser::parse_program(source).unwrap()).unwrap_err();
+    assert_eq!(
+        err.error,
+        CompileErrorType::TypeError(String::from("Range end must be uint"))
+    );
+    assert_eq!(err.location.column(), source.find("\"x\"").unwrap() + 1);
+}
+
+#[test]
+fn test_symbol_dump() {
+    let program = parser::parse_program(
+        "contract A { uint b; function f(uint a) { uint c = a; if c < 1 { bool d; }; } }",
+    )
+    .unwrap();
+    let (_, dump) = compile_program_with_symbols(&program).unwrap();
+    assert!(dump.contains("contract A:\n  b: Uint\n"));
+    assert!(dump.contains("function f:\n"));
+    assert!(dump.contains("  a: Uint\n"));
+    assert!(dump.contains("  c: Uint\n"));
+    assert!(dump.contains("  d: Bool\n"));
+}
+
+#[test]
+fn test_contract_diff() {
+    let compile = |source: &str| {
+        let program = parser::parse_program(source).unwrap();
+        compile_program(&program).unwrap().swap_remove("A").unwrap()
+    };
+    let contract = compile("contract A { uint b; function f() { b = 1; } }");
+    assert!(contract.diff(&contract).is_empty());
+
+    let other = compile("contract A { uint b; function f() { b = 1; } function g() {} }");
+    assert_eq!(contract.diff(&other), vec!["added function `g`"]);
+    assert_eq!(other.diff(&contract), vec!["removed function `g`"]);
+
+    let other = compile("contract A { bool b; function f() { b = true; } }");
+    assert_eq!(
+        contract.diff(&other),
+        vec!["changed member `b`: Uint -> Bool", "changed function `f`"]
+    );
+}
+
+#[test]
+fn test_compound_expression_value() {
+    let program =
+        parser::parse_program("contract A { function f() { uint a; a = { uint b = 1; b }; } }")
+            .unwrap();
+    let contracts = compile_program(&program).unwrap();
+    let function = contracts.get("A").unwrap().functions.get("f").unwrap();
+    let instructions = function.codes().instructions();
+    assert_eq!(instructions.len(), 3);
+    if let Instruction::Assign { dst, src } = &instructions[2] {
+        assert_eq!(dst.id, "a");
+        assert_eq!(src.id, "b");
+        assert_eq!(src.typ, Type::Uint);
+    } else {
+        panic!("expected Assign");
+    }
+
+    let program =
+        parser::parse_program("contract A { function f() { uint a; a = { 1 }; } }").unwrap();
+    let contracts = compile_program(&program).unwrap();
+    let function = contracts.get("A").unwrap().functions.get("f").unwrap();
+    if let Instruction::Assign { src, .. } = &function.codes().instructions()[1] {
+        assert_eq!(src.typ, Type::Uint);
+    } else {
+        panic!("expected Assign");
+    }
+}
+
+#[test]
+fn test_call_result_type() {
+    let program = parser::parse_program(
+        "contract A { function f() { uint x = g(); bool y = h(); } \
+         function g() returns (uint) { 1 } function h() returns (bool) {} }",
+    )
+    .unwrap();
+    let contracts = compile_program(&program).unwrap();
+    let function = contracts.get("A").unwrap().functions.get("f").unwrap();
+    if let Instruction::Call { dst, .. } = &function.codes().instructions()[0] {
+        assert_eq!(dst.typ, Type::Uint);
+    } else {
+        panic!("expected Call");
+    }
+
+    let program = parser::parse_program(
+        "contract A { function f() { bool x = g(); } function g() returns (uint) { 1 } }",
+    )
+    .unwrap();
+    assert!(compile_program(&program).is_err());
+}
+
+#[test]
+fn test_constant_member() {
+    assert!(
+        compile_source("contract A { uint constant a = 1 + 2; uint constant b = a * 2; }").is_ok()
+    );
+    assert!(compile_source("contract A { uint constant a; }").is_err());
+    assert!(compile_source("contract A { uint b = 1; uint constant a = b; }").is_err());
+    assert!(compile_source("contract A { uint constant a = 1; a = 2; }").is_err());
+    assert!(compile_source("contract A { uint constant a = 1; function f() { a = 2; } }").is_err());
+}
+
+#[test]
+fn test_immutable_member() {
+    assert!(compile_source("contract A { uint immutable a; a = 2; }").is_ok());
+    assert_eq!(
+        compile_source("contract A { uint immutable a = 1; function f() { a = a + 2; } }"),
+        Err(CompileErrorType::TypeError(String::from(
+            "Cannot assign to immutable `a` outside the constructor"
+        )))
+    );
+}
+
+#[test]
+fn test_free_function() {
+    let program = parser::parse_program(
+        "contract A { function f() { uint x = add(1, 2); } } \
+         function add(uint a, uint b) returns (uint) { a + b }",
+    )
+    .unwrap();
+    let result = compile(&program).unwrap();
+    let (contracts, free_functions) = (result.contracts, result.free_functions);
+    let add = free_functions.get("add").unwrap();
+    assert_eq!(add.params().len(), 2);
+    assert_eq!(*add.ret(), Type::Uint);
+    assert!(!contracts.get("A").unwrap().functions.contains_key("add"));
+    let function = contracts.get("A").unwrap().functions.get("f").unwrap();
+    if let Instruction::Call { func, dst, .. } = &function.codes().instructions()[0] {
+        assert_eq!(func.id, "add");
+        assert_eq!(dst.typ, Type::Uint);
+    } else {
+        panic!("expected Call");
+    }
+}
+
+#[test]
+fn test_generated_contract() {
+    let program = parser::parse_program(&generate_contract(5, 3)).unwrap();
+    let contracts = compile_program(&program).unwrap();
+    let contract = contracts.get("Generated").unwrap();
+    assert_eq!(contract.functions.len(), 5);
+    assert_eq!(*contract.functions["f4"].ret(), Type::Uint);
+}
+
+#[test]
+fn test_if_expression_type() {
+    assert!(compile_function("bool c = 1 < 2; uint x = if c { 1 } else { 2 };").is_ok());
+    assert!(compile_function("bool c = 1 < 2; bool x = if c { 1 } else { 2 };").is_err());
+    assert!(compile_function("bool c = 1 < 2; if c { 1 } else { c };").is_err());
+}
+
+#[test]
+fn test_assign_in_condition_warning() {
+    let assign = body_warnings("uint a; uint b;", "if a = b {};");
+    assert_eq!(assign.len(), 1);
+    assert_eq!(
+        assign[0].message,
+        "Assignment used as a condition, did you mean `==`?"
+    );
+    assert!(body_warnings("uint a; uint b;", "if a == b {};").is_empty());
+
+    let assign = body_warnings("bool a; bool b;", "while a = b {};");
+    assert_eq!(assign.len(), 1);
+    assert_eq!(
+        assign[0].message,
+        "Assignment used as a condition, did you mean `==`?"
+    );
+}
+
+#[test]
+fn test_self_comparison_warning() {
+    let same = body_warnings("uint a; uint b;", "a == a;");
+    assert_eq!(same.len(), 1);
+    assert_eq!(
+        same[0].message,
+        "Comparison of `a` with itself is always true"
+    );
+    assert_eq!(
+        body_warnings("uint a; uint b;", "a < a;")[0].message,
+        "Comparison of `a` with itself is always false"
+    );
+    assert!(body_warnings("uint a; uint b;", "a == b;").is_empty());
+}
+
+#[test]
+fn test_using_method_call() {
+    let program = parser::parse_program(
+        "contract Math { function double(uint a) returns (uint) { a * 2 } } \
+         contract A { using Math for uint; function f() { uint x = 1; uint y = x.double(); } }",
+    )
+    .unwrap();
+    let contracts = compile_program(&program).unwrap();
+    let function = contracts.get("A").unwrap().functions.get("f").unwrap();
+    if let Instruction::Call { func, args, dst } = &function.codes().instructions()[1] {
+        assert_eq!(func.id, "Math.double");
+        assert_eq!(args[0].id, "x");
+        assert_eq!(dst.typ, Type::Uint);
+    } else {
+        panic!("expected Call");
+    }
+
+    assert!(compile_source(
+        "contract Math { function double(uint a) returns (uint) { a * 2 } } \
+         contract A { using Math for bool; function f() { uint x = 1; x.double(); } }"
+    )
+    .is_err());
+    assert!(compile_source("contract A { using Math for uint; }").is_err());
+
+    let call = |library: &str, body: &str| {
+        compile_source(&format!(
+            "contract A {{ using L for uint; function f() {{ uint x = 1; {} }} }} \
+             contract L {{ {} }}",
+            body, library
+        ))
+    };
+    let inc = "function inc(uint a) returns (uint) { a + 1 }";
+    assert_eq!(call(inc, "x.inc();"), Ok(()));
+    assert_eq!(
+        call(inc, "x.inc(1, 2, 3);"),
+        Err(CompileErrorType::TypeError(String::from(
+            "`inc(uint)` cannot be called with (uint,uint,uint,uint)"
+        )))
+    );
+    assert_eq!(
+        call("function inc(address a) {}", "x.inc();"),
+        Err(CompileErrorType::TypeError(String::from(
+            "`inc(address)` cannot be called with (uint)"
+        )))
+    );
+    let overloads = "function inc(uint a) returns (uint) { a + 1 } \
+                     function inc(uint a, uint b) returns (uint) { a + b }";
+    assert_eq!(call(overloads, "uint y = x.inc(); y = x.inc(2);"), Ok(()));
+    assert_eq!(
+        call(overloads, "x.inc(true);"),
+        Err(CompileErrorType::TypeError(String::from(
+            "No overload of `inc` matches the arguments"
+        )))
+    );
+}
+
+#[test]
+fn test_for_step() {
+    let step = |range: &str| {
+        let source = format!(
+            "contract A {{ function f() {{ for i in {} {{ i; }}; }} }}",
+            range
+        );
+        let contracts = compile_program(&parser::parse_program(&source).unwrap()).unwrap();
+        match &contracts["A"].functions["f"].codes().instructions()[0] {
+            Instruction::For { step, .. } => step.value.clone(),
+            instruction => panic!("expected For, got {:?}", instruction),
+        }
+    };
+    assert_eq!(step("0..10"), Some(BigUint::from(1u32)));
+    assert_eq!(step("0..10 step 3"), Some(BigUint::from(3u32)));
+    assert!(compile_function("uint a; for i in 0..10 step 2 { a = a + i; };").is_ok());
+    assert!(compile_function("uint s = 2; for i in 0..10 step s + 1 { i; };").is_ok());
+    assert_eq!(
+        compile_function("for i in 0..10 step 0 { };"),
+        Err(CompileErrorType::ArithmeticError(String::from(
+            "Range step must not be zero"
+        )))
+    );
+}
+
+#[test]
+fn test_redundant_parentheses_warning() {
+    let nested = body_warnings("uint a; uint b; uint c;", "((a));");
+    assert_eq!(nested.len(), 2);
+    assert_eq!(nested[0].message, "Redundant parentheses");
+    assert_eq!(nested[0].location.column(), 53);
+    assert_eq!(body_warnings("uint a; uint b;", "(a) + b;").len(), 1);
+    assert!(body_warnings("uint a; uint b; uint c;", "(a + b) * c;").is_empty());
+}
+
+// Warnings other than unused variables, which the lint tests declare freely.
+fn lint_warnings(program: &Program) -> Vec<CompileWarning> {
+    compile(program)
+        .unwrap()
+        .warnings
+        .into_iter()
+        .filter(|warning| !warning.message.starts_with("Unused variable"))
+        .collect()
+}
+
+// Lint warnings of `body` in a function of a contract declaring the `prelude` members.
+fn body_warnings(prelude: &str, body: &str) -> Vec<CompileWarning> {
+    let source = format!("contract A {{ {} function f() {{ {} }} }}", prelude, body);
+    lint_warnings(&parser::parse_program(&source).unwrap())
+}
+
+#[test]
+fn test_compile_result() {
+    let program =
+        parser::parse_program("contract A { function f() { uint a = 1; uint b = a; } }").unwrap();
+    let result = compile(&program).unwrap();
+    assert!(result.contracts.contains_key("A"));
+    assert_eq!(result.warnings.len(), 1);
+    assert_eq!(result.warnings[0].message, "Unused variable `b`");
+    assert_eq!(result.warnings[0].location.column(), 41);
+    assert!(result.notes.is_empty());
+}
+
+#[test]
+fn test_duplicate_parameter() {
+    let program =
+        parser::parse_program("contract A { function f(uint a, bool b) { a; b; } }").unwrap();
+    let contracts = compile_program(&program).unwrap();
+    assert_eq!(contracts["A"].functions["f"].param_names(), vec!["a", "b"]);
+
+    let source = "contract A { function f(uint a, uint a) {} }";
+    let program = parser::parse_program(source).unwrap();
+    let err = compile_program(&program).unwrap_err();
+    assert_eq!(
+        err.error,
+        CompileErrorType::NameError(String::from("Duplicate parameter `a`"))
+    );
+    assert_eq!(err.location.column(), source.rfind("uint").unwrap() + 1);
+}
+
+#[test]
+fn test_iterator_shadow_warning() {
+    let shadow = body_warnings("uint m;", "bool a; for a in 0..10 {};");
+    assert_eq!(shadow.len(), 1);
+    assert_eq!(
+        shadow[0].message,
+        "Loop iterator `a` shadows an existing binding"
+    );
+    assert_eq!(body_warnings("uint m;", "for m in 0..10 {};").len(), 1);
+    assert!(body_warnings("uint m;", "for i in 0..10 {};").is_empty());
+}
+
+#[test]
+fn test_pragma() {
+    let program = parser::parse_program("pragma ziraffe 0.1; contract A {}").unwrap();
+    let result = compile(&program).unwrap();
+    assert_eq!(
+        result.pragmas,
+        vec![(String::from("ziraffe"), String::from("0.1"))]
+    );
+}
+
+#[test]
+fn test_print_ir() {
+    let source = "contract A { function f(uint a, uint b) { uint c = a + b * 2; } }";
+    let program = parser::parse_program(source).unwrap();
+    let contracts = compile_program(&program).unwrap();
+    let printed = contracts["A"].functions["f"].codes().to_string();
+    assert_eq!(
+        printed,
+        "{\n    mul_result_1 = b * 2\n    add_result_2 = a + mul_result_1\n    init c = add_result_2\n}"
+    );
+    // Hints only affect printing.
+    assert!(contracts["A"]
+        .diff(&compile_program(&program).unwrap()["A"])
+        .is_empty());
+}
+
+#[test]
+fn test_check_expression() {
+    assert!(check_expression("1 + 2", Type::Uint).is_ok());
+    assert!(check_expression("1 < 2", Type::Bool).is_ok());
+    assert_eq!(
+        check_expression("1 + 2", Type::Bool).unwrap_err().error,
+        CompileErrorType::TypeError(String::from("Expected Bool but got Uint"))
+    );
+    assert!(check_expression("1 +", Type::Uint).is_err());
+}
+
+#[test]
+fn test_unchecked_block() {
+    let program = parser::parse_program(
+        "contract A { function f() { uint a = 1; unchecked { a = a + 1; a = a / 2; }; \
+         a = a * 2; } }",
+    )
+    .unwrap();
+    let contracts = compile_program(&program).unwrap();
+    let codes = contracts["A"].functions["f"].codes().instructions();
+    match &codes[1] {
+        Instruction::Unchecked { block } => {
+            let block = block.instructions();
+            assert!(matches!(block[0], Instruction::WrappingAdd { .. }));
+            // Division cannot overflow, so it has no wrapping form.
+            assert!(matches!(block[2], Instruction::Div { .. }));
+        }
+        instruction => panic!("expected Unchecked, got {:?}", instruction),
+    }
+    // Arithmetic after the block is checked again.
+    assert!(matches!(codes[2], Instruction::Mul { .. }));
+}
+
+#[test]
+fn test_overloaded_function() {
+    let program = parser::parse_program(
+        "contract A { function f(uint a) returns (uint) { a } \
+         function f(address a) returns (bool) {} \
+         function g(address b) { uint x = f(1); bool y = f(b); } }",
+    )
+    .unwrap();
+    let contracts = compile_program(&program).unwrap();
+    let functions = &contracts["A"].functions;
+    assert_eq!(functions["f(uint)"].params()[0].typ, Type::Uint);
+    assert_eq!(functions["f(address)"].params()[0].typ, Type::Address);
+    let calls: Vec<(&str, &Type)> = functions["g"]
+        .codes()
+        .instructions()
+        .iter()
+        .filter_map(|instruction| match instruction {
+            Instruction::Call { func, dst, .. } => Some((func.id.as_str(), &dst.typ)),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(
+        calls,
+        vec![("f(uint)", &Type::Uint), ("f(address)", &Type::Bool)]
+    );
+    assert_eq!(
+        compile_source(
+            "contract A { function f(uint a) {} function f(address a) {} function g() { f(\"a\"); } }"
+        ),
+        Err(CompileErrorType::TypeError(String::from(
+            "No overload of `f` matches the arguments"
+        )))
+    );
+}
+
+#[test]
+fn test_public_member_getter() {
+    let program =
+        parser::parse_program("contract A { public uint balance; private bool paused; }").unwrap();
+    let contracts = compile_program(&program).unwrap();
+    let functions = &contracts["A"].functions;
+    let getter = &functions["balance"];
+    assert!(getter.params().is_empty());
+    assert_eq!(*getter.ret(), Type::Uint);
+    assert_eq!(getter.result().unwrap().id, "balance");
+    assert!(!functions.contains_key("paused"));
+    assert!(compile_source("contract A { public uint f; function f() {} }").is_err());
+}
+
+#[test]
+fn test_string_pool() {
+    let program = parser::parse_program(
+        "contract A { string s = \"error\"; \
+         function f() { string a = \"error\"; string b = \"other\"; } \
+         function g() { string c = \"error\"; } }",
+    )
+    .unwrap();
+    let contracts = compile_program(&program).unwrap();
+    let contract = &contracts["A"];
+    assert_eq!(contract.strings.len(), 2);
+    assert_eq!(contract.string_index("error"), Some(0));
+    assert_eq!(contract.string_index("other"), Some(1));
+}
+
+#[test]
+fn test_top_level_statement() {
+    let initializer = parser::parse_statement("uint a = 1").unwrap();
+    let members = Statement {
+        location: initializer.location,
+        node: StatementType::MemberStatement {
+            statements: vec![initializer],
+        },
+    };
+    let program = Program::GlobalStatements(vec![members]);
+    assert_eq!(
+        compile_program(&program).unwrap_err().error,
+        CompileErrorType::SyntaxError(String::from(
+            "Expected a contract, function or pragma at the top level"
+        ))
+    );
+}
+
+// Add `statement` to the members of the contract in `source`, or to the body of its
+// function as a member statement, which the parser never produces there.
+fn insert_member(source: &str, statement: Statement) -> Program {
+    let Program::GlobalStatements(mut stmts) = parser::parse_program(source).unwrap();
+    match &mut stmts[0].node {
+        StatementType::ContractStatement { members, .. } => {
+            let StatementType::MemberStatement { statements } = &mut members.node else {
+                panic!("expected members");
+            };
+            statements.insert(0, statement);
+        }
+        StatementType::FunctionStatement { expr, .. } => {
+            let ExpressionType::CompoundExpression { statements, .. } = &mut expr.node else {
+                panic!("expected a function body");
+            };
+            statements.push(Statement {
+                location: statement.location,
+                node: StatementType::MemberStatement {
+                    statements: vec![statement],
+                },
+            });
+        }
+        node => panic!("expected a contract or function, got {:?}", node),
+    }
+    Program::GlobalStatements(stmts)
+}
+
+#[test]
+fn test_member_outside_contract() {
+    let initializer = parser::parse_statement("uint a = 1").unwrap();
+    let program = insert_member("function f() {}", initializer);
+    assert_eq!(
+        compile_program(&program).unwrap_err().error,
+        CompileErrorType::SyntaxError(String::from("Member `a` declared outside a contract"))
+    );
+}
+
+#[test]
+fn test_nested_contract() {
+    let Program::GlobalStatements(mut inner) = parser::parse_program("contract B {}").unwrap();
+    let program = insert_member("contract A { uint c; }", inner.remove(0));
+    assert_eq!(
+        compile_program(&program).unwrap_err().error,
+        CompileErrorType::SyntaxError(String::from("Member `c` declared outside a contract"))
+    );
+    let Program::GlobalStatements(mut inner) = parser::parse_program("contract B {}").unwrap();
+    let program = insert_member("contract A {}", inner.remove(0));
+    assert_eq!(
+        compile_program(&program).unwrap_err().error,
+        CompileErrorType::SyntaxError(String::from("Expected to be in a contract"))
+    );
+}
+
+#[test]
+fn test_mod_operation() {
+    let program =
+        parser::parse_program("contract A { function f() { uint a = 0; uint b = 7; a = b % 3; } }")
+            .unwrap();
+    let contracts = compile_program(&program).unwrap();
+    let codes = contracts["A"].functions["f"].codes().instructions();
+    assert!(codes
+        .iter()
+        .any(|instruction| matches!(instruction, Instruction::Mod { .. })));
+    assert_eq!(
+        compile_function("uint a = 1; a % 0;"),
+        Err(CompileErrorType::ArithmeticError(String::from(
+            "Division by zero"
+        )))
+    );
+    assert!(compile_function("bool a = 1 < 2; a % a;").is_err());
+}
+
+#[test]
+fn test_compound_assign() {
+    for op in ["+", "-", "*", "/", "%"] {
+        let source = format!(
+            "contract A {{ function f() {{ uint a = 6; a {}= 2; }} }}",
+            op
+        );
+        let contracts = compile_program(&parser::parse_program(&source).unwrap()).unwrap();
+        let codes = contracts["A"].functions["f"].codes().instructions();
+        let (result, operands) = (codes[1].dst().unwrap(), codes[1].operands());
+        assert_eq!(operands[0].id, "a", "{}=", op);
+        assert_eq!(operands[1].id, "2", "{}=", op);
+        let expected = match op {
+            "+" => matches!(codes[1], Instruction::Add { .. }),
+            "-" => matches!(codes[1], Instruction::Sub { .. }),
+            "*" => matches!(codes[1], Instruction::Mul { .. }),
+            "/" => matches!(codes[1], Instruction::Div { .. }),
+            _ => matches!(codes[1], Instruction::Mod { .. }),
+        };
+        assert!(expected, "{}= compiled to {:?}", op, codes[1]);
+        match &codes[2] {
+            Instruction::Assign { dst, src } => {
+                assert_eq!(dst.id, "a");
+                assert_eq!(**src, *result);
+            }
+            instruction => panic!("expected Assign, got {:?}", instruction),
+        }
+    }
+    assert_eq!(
+        compile_function("uint a = 1; a /= 0;"),
+        Err(CompileErrorType::ArithmeticError(String::from(
+            "Division by zero"
+        )))
+    );
+}
+
+#[test]
+fn test_unary_operation() {
+    let program = parser::parse_program(
+        "contract A { function f() { uint a = 1; uint b = -a; bool c = a < b; bool d = !c; } }",
+    )
+    .unwrap();
+    let contracts = compile_program(&program).unwrap();
+    let codes = contracts["A"].functions["f"].codes().instructions();
+    match &codes[1] {
+        Instruction::Neg { dst, src } => {
+            assert_eq!(dst.typ, Type::Uint);
+            assert_eq!(src.id, "a");
+        }
+        instruction => panic!("expected Neg, got {:?}", instruction),
+    }
+    assert!(codes.iter().any(|instruction| matches!(
+        instruction,
+        Instruction::Not { dst, .. } if dst.typ == Type::Bool
+    )));
+    assert_eq!(
+        compile_function("bool a = 1 < 2; -a;"),
+        Err(CompileErrorType::TypeError(String::from(
+            "Operator `-` expects Uint but got Bool"
+        )))
+    );
+    assert_eq!(
+        compile_function("uint a = 1; !a;"),
+        Err(CompileErrorType::TypeError(String::from(
+            "Operator `!` expects Bool but got Uint"
+        )))
+    );
+}
+
+#[test]
+fn test_bool_literal() {
+    let program =
+        parser::parse_program("contract A { bool b = true; function f() { b = false; } }").unwrap();
+    let contracts = compile_program(&program).unwrap();
+    match &contracts["A"].constructor.instructions()[0] {
+        Instruction::InitAssign { src, .. } => {
+            assert_eq!(src.id, "true");
+            assert_eq!(src.typ, Type::Bool);
+        }
+        instruction => panic!("expected InitAssign, got {:?}", instruction),
+    }
+    assert!(compile_function("bool c = false; if c { 1 } else { 2 };").is_ok());
+    assert_eq!(
+        compile_function("string s = true;"),
+        Err(CompileErrorType::TypeError(String::from(
+            "Expected String but got Bool"
+        )))
+    );
+}
+
+#[test]
+fn test_while_loop() {
+    let program = parser::parse_program(
+        "contract A { function f() { uint a = 0; while a < 10 { a = a + 1; }; } }",
+    )
+    .unwrap();
+    let contracts = compile_program(&program).unwrap();
+    let codes = contracts["A"].functions["f"].codes().instructions();
+    let (cond, block) = match &codes[2] {
+        Instruction::While { cond, block } => (cond, block),
+        instruction => panic!("expected While, got {:?}", instruction),
+    };
+    assert_eq!(cond.typ, Type::Bool);
+    assert_eq!(codes[1].dst(), Some(&**cond));
+    // The body ends by re-evaluating the condition into `cond`.
+    let body = block.instructions();
+    assert!(matches!(body[body.len() - 2], Instruction::Lt { .. }));
+    match &body[body.len() - 1] {
+        Instruction::Assign { dst, .. } => assert_eq!(**dst, **cond),
+        instruction => panic!("expected Assign, got {:?}", instruction),
+    }
+    assert!(compile_function("bool c = true; while c { c = false; };").is_ok());
+    assert_eq!(
+        compile_function("uint a = 1; while a { a = 0; };"),
+        Err(CompileErrorType::TypeError(String::from(
+            "While condition must be Bool but got Uint"
+        )))
+    );
+}
+
+#[test]
+fn test_return_statement() {
+    let program = parser::parse_program(
+        "contract A { function f() returns (uint) { return 1; } function g() { return; } }",
+    )
+    .unwrap();
+    let contracts = compile_program(&program).unwrap();
+    let codes = contracts["A"].functions["f"].codes().instructions();
+    match &codes[0] {
+        Instruction::Return { value: Some(value) } => assert_eq!(value.typ, Type::Uint),
+        instruction => panic!("expected Return, got {:?}", instruction),
+    }
+    let codes = contracts["A"].functions["g"].codes().instructions();
+    assert!(matches!(codes[0], Instruction::Return { value: None }));
+}
+
+#[test]
+fn test_return_type_mismatch() {
+    let compile = |source: &str| {
+        compile_program(&parser::parse_program(source).unwrap())
+            .map(|_| ())
+            .map_err(|err| err.error)
+    };
+    assert_eq!(
+        compile("contract A { function f() returns (uint) { return \"x\"; } }"),
+        Err(CompileErrorType::TypeError(String::from(
+            "Function `f` returns Uint but got String"
+        )))
+    );
+    assert_eq!(
+        compile("contract A { function f() returns (uint) { return; } }"),
+        Err(CompileErrorType::TypeError(String::from(
+            "Function `f` must return Uint"
+        )))
+    );
+    assert_eq!(
+        compile_function("return 1;"),
+        Err(CompileErrorType::TypeError(String::from(
+            "Function `f` does not return a value"
+        )))
+    );
+}
+
+#[test]
+fn test_else_if_chain() {
+    let program = parser::parse_program(
+        "contract A { function f() returns (uint) { uint a = 3; \
+         if a < 2 { 1 } else if a < 4 { 2 } else { 3 } } }",
+    )
+    .unwrap();
+    let contracts = compile_program(&program).unwrap();
+    let codes = contracts["A"].functions["f"].codes().instructions();
+    let Some(Instruction::If {
+        then_block,
+        else_block: Some(else_block),
+        ..
+    }) = codes
+        .iter()
+        .find(|code| matches!(code, Instruction::If { .. }))
+    else {
+        panic!("expected If with an else branch, got {:?}", codes);
+    };
+    assert!(matches!(
+        then_block.instructions().last(),
+        Some(Instruction::Assign { .. })
+    ));
+    // The `else if` is an `If` nested in the else branch, which yields its value.
+    let nested = else_block.instructions();
+    assert!(matches!(
+        nested
+            .iter()
+            .find(|code| matches!(code, Instruction::If { .. })),
+        Some(Instruction::If {
+            else_block: Some(_),
+            ..
+        })
+    ));
+    assert!(matches!(nested.last(), Some(Instruction::Assign { .. })));
+    assert_eq!(
+        compile_function("uint a = 3; if a < 2 { 1 } else if a < 4 { 2 } else { a < 1 };"),
+        Err(CompileErrorType::TypeError(String::from(
+            "If branches have different types: Uint and Bool"
+        )))
+    );
+}
+
+#[test]
+fn test_array_literal() {
+    let program =
+        parser::parse_program("contract A { function f() { uint[3] xs = [1, 2, 3]; xs; } }")
+            .unwrap();
+    let contracts = compile_program(&program).unwrap();
+    let codes = contracts["A"].functions["f"].codes().instructions();
+    match &codes[0] {
+        Instruction::Array { dst, elements } => {
+            assert_eq!(dst.typ, Type::Array(Box::new(Type::Uint), 3));
+            assert_eq!(elements.len(), 3);
+        }
+        instruction => panic!("expected Array, got {:?}", instruction),
+    }
+    assert!(compile_function("bool[2] bs = [true, 1 < 2]; bs;").is_ok());
+    assert_eq!(
+        compile_function("uint[2] xs = [1, \"a\"];"),
+        Err(CompileErrorType::TypeError(String::from(
+            "Array elements have different types: Uint and String"
+        )))
+    );
+    assert_eq!(
+        compile_function("uint[2] xs = [1, 2, 3];"),
+        Err(CompileErrorType::TypeError(String::from(
+            "Expected Array(Uint, 2) but got Array(Uint, 3)"
+        )))
+    );
+    assert_eq!(
+        compile_function("uint[2] a = [1, 2]; uint[2] b = [3, 4]; uint[2] c = a + b;"),
+        Err(CompileErrorType::TypeError(String::from(
+            "Array only supports equality comparison"
+        )))
+    );
+    assert!(compile_function("uint[2] a = [1, 2]; uint[2] b = [3, 4]; a == b;").is_ok());
+    assert_eq!(
+        compile_function("uint[2] xs; xs = [1, 2, 3];"),
+        Err(CompileErrorType::TypeError(String::from(
+            "Expected Array(Uint, 2) but got Array(Uint, 3)"
+        )))
+    );
+    assert_eq!(
+        compile_function("uint[0] xs = [];"),
+        Err(CompileErrorType::TypeError(String::from(
+            "Cannot infer the element type of an empty array"
+        )))
+    );
+}
+
+#[test]
+fn test_function_arguments() {
+    let source = |call: &str| {
+        format!(
+            "contract A {{ function f(uint a) {{}} function g() {{ {}; }} }}",
+            call
+        )
+    };
+    assert!(compile_source(&source("f(1)")).is_ok());
+    assert_eq!(
+        compile_source(&source("f(1, 2, true)")),
+        Err(CompileErrorType::TypeError(String::from(
+            "`f(uint)` cannot be called with (uint,uint,bool)"
+        )))
+    );
+    assert_eq!(
+        compile_source(&source("f()")),
+        Err(CompileErrorType::TypeError(String::from(
+            "`f(uint)` cannot be called with ()"
+        )))
+    );
+    assert_eq!(
+        compile_source("contract A { function f(uint a) {} function f(uint b) {} }"),
+        Err(CompileErrorType::NameError(String::from(
+            "Function `f(uint)` is declared more than once"
+        )))
+    );
+    assert_eq!(
+        compile_source("function f(uint[3] a) {} function f(uint a) {}"),
+        Ok(())
+    );
 }
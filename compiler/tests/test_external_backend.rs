@@ -0,0 +1,32 @@
+use num_bigint::BigUint;
+use ziraffe_compiler::instruction::Instruction;
+use ziraffe_compiler::symbol_table::{Symbol, Type};
+
+// Renders operands the way an out-of-crate backend would, from public fields only.
+fn render(symbol: &Symbol) -> String {
+    match (&symbol.value, symbol.is_temp()) {
+        (Some(value), _) => format!("#{}", value),
+        (None, true) => format!("%{}", symbol.num),
+        (None, false) => format!("${}:{:?}", symbol.id, symbol.typ),
+    }
+}
+
+#[test]
+fn test_format_symbol_without_context() {
+    let mut temp = Symbol::named(String::new(), Type::Uint);
+    temp.num = 7;
+    let a = Symbol::named(String::from("a"), Type::Uint);
+    let two = Symbol::number(&BigUint::from(2u32));
+    assert_eq!(render(&temp), "%7");
+    assert_eq!(render(&a), "$a:Uint");
+    assert_eq!(render(&two), "#2");
+
+    let add = Instruction::Add {
+        dst: Box::new(temp),
+        left: Box::new(a),
+        right: Box::new(two),
+    };
+    let operands: Vec<String> = add.operands().into_iter().map(render).collect();
+    assert_eq!(operands, vec!["$a:Uint", "#2"]);
+    assert_eq!(add.to_string(), "_7 = a + 2");
+}
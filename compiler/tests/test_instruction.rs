@@ -0,0 +1,84 @@
+use ziraffe_compiler::error::CompileErrorType;
+use ziraffe_compiler::instruction::{Block, Instruction};
+use ziraffe_compiler::symbol_table::{Symbol, Type};
+use ziraffe_parser::ast::Operator;
+use ziraffe_parser::location::Location;
+
+#[test]
+fn test_assign_is_not_binary_operator() {
+    let symbol = Symbol::literal_symbol(String::from("a"));
+    let res = Instruction::get_instruction_from_bin_op(
+        Operator::Assign,
+        symbol.clone(),
+        symbol.clone(),
+        symbol,
+        Location::new(1, 3),
+    );
+    let err = res.unwrap_err();
+    assert_eq!(
+        err.error,
+        CompileErrorType::SyntaxError(String::from("Assignment is not a binary operator"))
+    );
+    assert_eq!(err.location, Location::new(1, 3));
+}
+
+#[test]
+fn test_instruction_operands() {
+    let symbol = |name: &str| Symbol::named(String::from(name), Type::Uint);
+    let add = Instruction::Add {
+        dst: Box::new(symbol("c")),
+        left: Box::new(symbol("a")),
+        right: Box::new(symbol("b")),
+    };
+    assert_eq!(add.dst(), Some(&symbol("c")));
+    assert_eq!(add.operands(), vec![&symbol("a"), &symbol("b")]);
+
+    let cond = Symbol::named(String::from("cond"), Type::Bool);
+    let branch = Instruction::If {
+        cond: Box::new(cond.clone()),
+        then_block: Box::new(Block::new()),
+        else_block: Some(Box::new(Block::new())),
+    };
+    assert_eq!(branch.dst(), None);
+    assert_eq!(branch.operands(), vec![&cond]);
+}
+
+#[test]
+fn test_instruction_operands_mut() {
+    let symbol = |name: &str| Symbol::named(String::from(name), Type::Uint);
+    let mut add = Instruction::Add {
+        dst: Box::new(symbol("c")),
+        left: Box::new(symbol("a")),
+        right: Box::new(symbol("b")),
+    };
+    add.operands_mut()[0].id = String::from("x");
+    add.dst_mut().unwrap().id = String::from("y");
+    assert_eq!(add.operands(), vec![&symbol("x"), &symbol("b")]);
+    assert_eq!(add.dst(), Some(&symbol("y")));
+}
+
+#[test]
+fn test_block_extend() {
+    let assign = |dst: &str, src: &str| Instruction::Assign {
+        dst: Box::new(Symbol::named(String::from(dst), Type::Uint)),
+        src: Box::new(Symbol::named(String::from(src), Type::Uint)),
+    };
+    let mut first = Block::new();
+    first.add_instruction(assign("a", "b"), Location::new(1, 1));
+    let mut second = Block::new();
+    second.add_instruction(assign("c", "d"), Location::new(2, 1));
+    second.add_instruction(assign("e", "f"), Location::new(3, 1));
+    first.extend(second);
+    assert_eq!(
+        first.instructions(),
+        &[assign("a", "b"), assign("c", "d"), assign("e", "f")]
+    );
+    assert_eq!(
+        first.locations(),
+        &[
+            Location::new(1, 1),
+            Location::new(2, 1),
+            Location::new(3, 1)
+        ]
+    );
+}
@@ -0,0 +1,89 @@
+use ziraffe_compiler::compiler::compile_program;
+use ziraffe_compiler::instruction::Instruction;
+use ziraffe_compiler::optimize::{cse, eliminate_self_assign, validate};
+use ziraffe_compiler::symbol_table::{Function, Type};
+use ziraffe_parser::parser;
+
+#[test]
+fn test_eliminate_self_assign() {
+    let program = parser::parse_program(
+        "contract A { function f() { uint a = 0; uint b = 1; a = a; a = b; } }",
+    )
+    .unwrap();
+    let mut contracts = compile_program(&program).unwrap();
+    let function = contracts
+        .get_mut("A")
+        .unwrap()
+        .functions
+        .get_mut("f")
+        .unwrap();
+    eliminate_self_assign(function.codes_mut());
+    let assigns: Vec<&Instruction> = function
+        .codes()
+        .instructions()
+        .iter()
+        .filter(|instruction| matches!(instruction, Instruction::Assign { .. }))
+        .collect();
+    assert_eq!(assigns.len(), 1);
+    if let Instruction::Assign { dst, src } = assigns[0] {
+        assert_eq!(dst.id, "a");
+        assert_eq!(src.id, "b");
+    }
+}
+
+fn count_add(function: &Function) -> usize {
+    function
+        .codes()
+        .all_instructions()
+        .into_iter()
+        .filter(|instruction| matches!(instruction, Instruction::Add { .. }))
+        .count()
+}
+
+#[test]
+fn test_cse() {
+    let program = parser::parse_program(
+        "contract A { \
+         function f() { uint a = 1; uint b = 2; uint c = a + b; uint d = a + b; } \
+         function g() { uint a = 1; uint b = 2; uint c = a + b; a = 3; uint d = a + b; } }",
+    )
+    .unwrap();
+    let mut contracts = compile_program(&program).unwrap();
+    let contract = contracts.get_mut("A").unwrap();
+
+    let f = contract.functions.get_mut("f").unwrap();
+    assert_eq!(count_add(f), 2);
+    cse(f);
+    assert_eq!(count_add(f), 1);
+
+    let g = contract.functions.get_mut("g").unwrap();
+    cse(g);
+    assert_eq!(count_add(g), 2);
+}
+
+#[test]
+fn test_validate() {
+    let program = parser::parse_program(
+        "contract A { function f() { uint a = 1; uint b = 2; uint c = a + b; uint d = a + b; \
+         if c == d { c = g(); }; } function g() returns (uint) { 1 } }",
+    )
+    .unwrap();
+    let mut contracts = compile_program(&program).unwrap();
+    let function = contracts
+        .get_mut("A")
+        .unwrap()
+        .functions
+        .get_mut("f")
+        .unwrap();
+    cse(function);
+    eliminate_self_assign(function.codes_mut());
+    assert!(validate(function).is_ok());
+
+    // A pass that swaps in a symbol of the wrong type is caught.
+    for instruction in function.codes_mut().instructions_mut() {
+        if let Instruction::InitAssign { src, .. } = instruction {
+            src.typ = Type::Bool;
+        }
+    }
+    assert!(validate(function).is_err());
+}
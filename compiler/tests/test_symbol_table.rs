@@ -0,0 +1,138 @@
+use num_bigint::BigUint;
+use std::collections::HashMap;
+use ziraffe_compiler::error::CompileErrorType;
+use ziraffe_compiler::instruction::Block;
+use ziraffe_compiler::symbol_table::{Context, Function, Symbol, Type};
+use ziraffe_parser::lexer::{make_tokenizer, Tok};
+use ziraffe_parser::location::Location;
+
+#[test]
+fn test_symbol_as_map_key() {
+    let a = Symbol::named(String::from("a"), Type::Uint);
+    let b = Symbol::named(String::from("b"), Type::Bool);
+    let mut slots = HashMap::new();
+    slots.insert(a.clone(), 0);
+    slots.insert(b.clone(), 1);
+    assert_eq!(slots.get(&a), Some(&0));
+    assert_eq!(slots.get(&b), Some(&1));
+
+    let mut types = HashMap::new();
+    types.insert(Type::Uint, "uint");
+    assert_eq!(types.get(&a.typ), Some(&"uint"));
+    assert_eq!(types.get(&b.typ), None);
+}
+
+#[test]
+fn test_symbol_constructors() {
+    let named = Symbol::named(String::from("a"), Type::Address);
+    assert_eq!(named.id, "a");
+    assert_eq!(named.num, 0);
+    assert_eq!(named.typ, Type::Address);
+    assert_eq!(named.value, None);
+
+    let undefined = Symbol::undefined("b");
+    assert_eq!(undefined.id, "b");
+    assert_eq!(undefined.typ, Type::Undefined);
+    assert_eq!(undefined.value, None);
+
+    let number = BigUint::from(42u32);
+    let symbol = Symbol::number(&number);
+    assert_eq!(symbol.id, "42");
+    assert_eq!(symbol.typ, Type::Uint);
+    assert_eq!(symbol.value, Some(number));
+}
+
+#[test]
+fn test_number_symbol_canonical_id() {
+    let number = |source: &str| match make_tokenizer(source).next().unwrap().unwrap().1 {
+        Tok::Num { number } => Symbol::number(&number),
+        tok => panic!("expected a number, got {:?}", tok),
+    };
+    assert_eq!(number("0xff").id, "255");
+    assert_eq!(number("0xff"), number("255"));
+}
+
+#[test]
+fn test_type_join() {
+    assert_eq!(Type::Uint.join(&Type::Uint), Some(Type::Uint));
+    assert_eq!(Type::Uint.join(&Type::Never), Some(Type::Uint));
+    assert_eq!(Type::Never.join(&Type::Bool), Some(Type::Bool));
+    assert_eq!(Type::Uint.join(&Type::Bool), None);
+}
+
+#[test]
+fn test_temp_limit() {
+    let mut context = Context::new();
+    context.max_temp = Some(2);
+    let location = Location::new(1, 1);
+    assert_eq!(Symbol::temp_symbol(&mut context, location).unwrap().num, 1);
+    assert_eq!(Symbol::temp_symbol(&mut context, location).unwrap().num, 2);
+    let err = Symbol::temp_symbol(&mut context, location).unwrap_err();
+    assert_eq!(
+        err.error,
+        CompileErrorType::LimitError(String::from("Too many temporaries in program"))
+    );
+    assert_eq!(context.temp_number, 2);
+}
+
+#[test]
+fn test_numbering_label() {
+    let mut context = Context::new();
+    let location = Location::new(1, 1);
+    Symbol::temp_symbol(&mut context, location).unwrap();
+    Symbol::temp_symbol(&mut context, location).unwrap();
+    assert_eq!(context.numbering_label(), 1);
+    assert_eq!(context.numbering_label(), 2);
+    assert_eq!(Symbol::temp_symbol(&mut context, location).unwrap().num, 3);
+}
+
+#[test]
+fn test_type_is_dynamic() {
+    assert!(Type::String.is_dynamic());
+    assert!(Type::URL.is_dynamic());
+    assert!(Type::JSON.is_dynamic());
+    assert!(Type::Bytes.is_dynamic());
+    assert!(!Type::Uint.is_dynamic());
+    assert!(!Type::Bool.is_dynamic());
+    assert!(!Type::Address.is_dynamic());
+}
+
+#[test]
+fn test_symbol_hint_ignored_in_equality() {
+    let mut context = Context::new();
+    let a = Symbol::temp_symbol(&mut context, Location::new(1, 1)).unwrap();
+    let mut b = a.clone();
+    b.hint = Some(String::from("add_result"));
+    assert_eq!(a, b);
+    assert_eq!(a.to_string(), "_1");
+    assert_eq!(b.to_string(), "add_result_1");
+    let mut set = std::collections::HashSet::new();
+    set.insert(a);
+    assert!(set.contains(&b));
+}
+
+#[test]
+fn test_symbol_refine_type() {
+    let symbol = Symbol::undefined("a").with_type(Type::Uint);
+    assert_eq!(symbol.typ, Type::Uint);
+    let mut symbol = Symbol::named(String::from("b"), Type::None);
+    symbol.set_type(Type::Bool);
+    assert_eq!(symbol.typ, Type::Bool);
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "cannot change the type of `a` from Uint to Bool")]
+fn test_symbol_change_concrete_type() {
+    Symbol::named(String::from("a"), Type::Uint).set_type(Type::Bool);
+}
+
+#[test]
+fn test_function_signature() {
+    let params = vec![
+        Symbol::named(String::from("a"), Type::Uint),
+        Symbol::named(String::from("b"), Type::Array(Box::new(Type::Address), 3)),
+    ];
+    let function = Function::new(String::from("f"), params, Type::None, Block::new());
+    assert_eq!(function.signature(), "f(uint,address[3])");
+}
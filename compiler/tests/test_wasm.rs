@@ -0,0 +1,42 @@
+use ziraffe_compiler::backend::wasm;
+use ziraffe_compiler::compiler::compile_program;
+use ziraffe_parser::parser;
+
+#[test]
+fn test_emit_wasm() {
+    let program = parser::parse_program(
+        "contract A { function add(uint a, uint b) returns (uint) { a + b } \
+         function name() returns (string) { \"a\" } }",
+    )
+    .unwrap();
+    let contracts = compile_program(&program).unwrap();
+    let module = wasm::emit(contracts.get("A").unwrap());
+    assert_eq!(&module[..8], b"\0asm\x01\x00\x00\x00");
+    // `local.get 0; local.get 1; i64.add`
+    assert!(module.windows(5).any(|w| w == [0x20, 0, 0x20, 1, 0x7c]));
+    assert!(module.windows(3).any(|w| w == b"add"));
+    assert!(!module.windows(4).any(|w| w == b"name"));
+}
+
+// An export entry is the length-prefixed name followed by the function kind.
+fn exports(module: &[u8], name: &str) -> bool {
+    let mut entry = vec![name.len() as u8];
+    entry.extend(name.as_bytes());
+    entry.push(0);
+    module.windows(entry.len()).any(|w| w == &entry[..])
+}
+
+#[test]
+fn test_emit_wasm_skips_storage() {
+    let program = parser::parse_program(
+        "contract A { public uint total; function f() returns (uint) { total } \
+         function g(uint a) { total = a; } function h(uint a) returns (uint) { a * 2 } }",
+    )
+    .unwrap();
+    let contracts = compile_program(&program).unwrap();
+    let module = wasm::emit(contracts.get("A").unwrap());
+    assert!(!exports(&module, "f"));
+    assert!(!exports(&module, "g"));
+    assert!(!exports(&module, "total"));
+    assert!(exports(&module, "h"));
+}
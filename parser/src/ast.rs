@@ -4,20 +4,29 @@ use crate::location::Location;
 
 // https://rust-lang.github.io/rust-clippy/master/index.html#large_enum_variant
 #[allow(clippy::large_enum_variant)]
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum Program {
     GlobalStatements(Vec<Statement>),
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Located<T> {
     pub location: Location,
     pub node: T,
 }
 
+impl<T> Located<T> {
+    pub fn map<U>(self, f: impl FnOnce(T) -> U) -> Located<U> {
+        Located {
+            location: self.location,
+            node: f(self.node),
+        }
+    }
+}
+
 pub type Statement = Located<StatementType>;
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum StatementType {
     // Global Statement
     FunctionStatement {
@@ -32,9 +41,21 @@ pub enum StatementType {
     },
     InitializerStatement {
         variable_type: Type,
+        mutability: Mutability,
+        visibility: Visibility,
         variable: Box<Expression>,
         default: Option<Box<Expression>>,
     },
+    // File-level `pragma key value;`
+    PragmaStatement {
+        key: String,
+        value: String,
+    },
+    // `using Lib for Type;` attaches the functions of `Lib` as methods.
+    UsingStatement {
+        library: Box<Expression>,
+        typ: Type,
+    },
     // Local Statement
     MemberStatement {
         statements: Vec<Statement>,
@@ -42,11 +63,14 @@ pub enum StatementType {
     Expression {
         expression: Box<Expression>,
     },
+    Return {
+        value: Option<Box<Expression>>,
+    },
 }
 
 pub type Expression = Located<ExpressionType>;
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum ExpressionType {
     CompoundExpression {
         statements: Vec<Statement>,
@@ -66,9 +90,15 @@ pub enum ExpressionType {
         function_name: Box<Expression>,
         arguments: Box<Expression>,
     },
+    MethodCallExpression {
+        object: Box<Expression>,
+        method: Box<Expression>,
+        arguments: Box<Expression>,
+    },
     IfExpression {
         condition: Box<Expression>,
         if_expr: Box<Expression>,
+        // Either a block or a chained `if`.
         else_expr: Option<Box<Expression>>,
     },
     ForEachExpression {
@@ -76,28 +106,91 @@ pub enum ExpressionType {
         vector: Box<Expression>,
         for_expr: Box<Expression>,
     },
+    WhileExpression {
+        condition: Box<Expression>,
+        body: Box<Expression>,
+    },
+    UncheckedExpression {
+        block: Box<Expression>,
+    },
+    ParenExpression {
+        expression: Box<Expression>,
+    },
+    UnaryExpression {
+        operator: UnaryOperator,
+        operand: Box<Expression>,
+    },
     Parameters {
         parameters: Vec<Statement>,
     },
     Arguments {
         arguments: Vec<Expression>,
     },
+    ArrayLiteral {
+        elements: Vec<Expression>,
+    },
     Range {
-        start: BigUint,
-        end: BigUint,
+        start: Box<Expression>,
+        end: Box<Expression>,
+        // Defaults to 1.
+        step: Option<Box<Expression>>,
     },
     Literal {
         value: String,
     },
+    HexLiteral {
+        value: String,
+    },
     Number {
         value: BigUint,
     },
+    Bool {
+        value: bool,
+    },
     Identifier {
         value: String,
     },
 }
 
+impl Operator {
+    pub fn is_logical(&self) -> bool {
+        matches!(self, Operator::And | Operator::Or)
+    }
+
+    pub fn is_equality(&self) -> bool {
+        matches!(self, Operator::Eq | Operator::NotEq)
+    }
+
+    pub fn is_comparison(&self) -> bool {
+        matches!(
+            self,
+            Operator::Lt
+                | Operator::Le
+                | Operator::Gt
+                | Operator::Ge
+                | Operator::Eq
+                | Operator::NotEq
+        )
+    }
+}
+
 impl ExpressionType {
+    /// Whether the expression binds tighter than any operator, so parentheses around it are redundant.
+    pub fn is_atomic(&self) -> bool {
+        matches!(
+            self,
+            ExpressionType::ParenExpression { .. }
+                | ExpressionType::FunctionCallExpression { .. }
+                | ExpressionType::MethodCallExpression { .. }
+                | ExpressionType::Literal { .. }
+                | ExpressionType::HexLiteral { .. }
+                | ExpressionType::Number { .. }
+                | ExpressionType::Bool { .. }
+                | ExpressionType::ArrayLiteral { .. }
+                | ExpressionType::Identifier { .. }
+        )
+    }
+
     pub fn identifier_name(&self) -> Option<String> {
         if let ExpressionType::Identifier { value } = self {
             Some(value.clone())
@@ -114,6 +207,7 @@ pub enum Operator {
     Sub,
     Mul,
     Div,
+    Mod,
     Pow,
     // Assign operator
     Assign,
@@ -131,7 +225,15 @@ pub enum Operator {
     NotEq,
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
+pub enum UnaryOperator {
+    // -
+    Neg,
+    // !
+    Not,
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub enum Type {
     // type
     URL,
@@ -141,4 +243,201 @@ pub enum Type {
     Bool,
     String,
     Address,
+    Bytes,
+    // Fixed-size array of the element type
+    Array(Box<Type>, usize),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Mutability {
+    Mutable,
+    // Folded at compile time.
+    Constant,
+    // Written only by the constructor.
+    Immutable,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Visibility {
+    // Gets a generated getter.
+    Public,
+    Private,
+}
+
+/// Lower syntactic sugar to core nodes before compilation.
+///
+/// Compound assignment `a op= b` becomes `a = a op b`.
+pub fn desugar(program: Program) -> Program {
+    match program {
+        Program::GlobalStatements(stmts) => {
+            Program::GlobalStatements(stmts.into_iter().map(desugar_stmt).collect())
+        }
+    }
+}
+
+fn desugar_stmt(stmt: Statement) -> Statement {
+    stmt.map(|node| match node {
+        StatementType::FunctionStatement {
+            function_name,
+            parameters,
+            expr,
+            returns,
+        } => StatementType::FunctionStatement {
+            function_name,
+            parameters: desugar_box(parameters),
+            expr: desugar_box(expr),
+            returns,
+        },
+        StatementType::ContractStatement {
+            contract_name,
+            members,
+        } => StatementType::ContractStatement {
+            contract_name,
+            members: Box::new(desugar_stmt(*members)),
+        },
+        StatementType::InitializerStatement {
+            variable_type,
+            mutability,
+            visibility,
+            variable,
+            default,
+        } => StatementType::InitializerStatement {
+            variable_type,
+            mutability,
+            visibility,
+            variable,
+            default: default.map(desugar_box),
+        },
+        StatementType::PragmaStatement { key, value } => {
+            StatementType::PragmaStatement { key, value }
+        }
+        StatementType::UsingStatement { library, typ } => {
+            StatementType::UsingStatement { library, typ }
+        }
+        StatementType::MemberStatement { statements } => StatementType::MemberStatement {
+            statements: statements.into_iter().map(desugar_stmt).collect(),
+        },
+        StatementType::Expression { expression } => StatementType::Expression {
+            expression: desugar_box(expression),
+        },
+        StatementType::Return { value } => StatementType::Return {
+            value: value.map(desugar_box),
+        },
+    })
+}
+
+// Sub-expressions are boxed in the AST already.
+#[allow(clippy::boxed_local)]
+fn desugar_box(expr: Box<Expression>) -> Box<Expression> {
+    Box::new(desugar_expr(*expr))
+}
+
+/// `desugar` for a single expression.
+pub fn desugar_expr(expr: Expression) -> Expression {
+    let location = expr.location;
+    expr.map(|node| match node {
+        ExpressionType::CompoundExpression {
+            statements,
+            return_value,
+        } => ExpressionType::CompoundExpression {
+            statements: statements.into_iter().map(desugar_stmt).collect(),
+            return_value: return_value.map(desugar_box),
+        },
+        ExpressionType::AssignExpression {
+            left,
+            operator,
+            right,
+        } => {
+            let right = desugar_box(right);
+            let right = if operator == Operator::Assign {
+                right
+            } else {
+                Box::new(Expression {
+                    location,
+                    node: ExpressionType::BinaryExpression {
+                        left: left.clone(),
+                        operator,
+                        right,
+                    },
+                })
+            };
+            ExpressionType::AssignExpression {
+                left,
+                operator: Operator::Assign,
+                right,
+            }
+        }
+        ExpressionType::BinaryExpression {
+            left,
+            operator,
+            right,
+        } => ExpressionType::BinaryExpression {
+            left: desugar_box(left),
+            operator,
+            right: desugar_box(right),
+        },
+        ExpressionType::FunctionCallExpression {
+            function_name,
+            arguments,
+        } => ExpressionType::FunctionCallExpression {
+            function_name,
+            arguments: desugar_box(arguments),
+        },
+        ExpressionType::MethodCallExpression {
+            object,
+            method,
+            arguments,
+        } => ExpressionType::MethodCallExpression {
+            object: desugar_box(object),
+            method,
+            arguments: desugar_box(arguments),
+        },
+        ExpressionType::IfExpression {
+            condition,
+            if_expr,
+            else_expr,
+        } => ExpressionType::IfExpression {
+            condition: desugar_box(condition),
+            if_expr: desugar_box(if_expr),
+            else_expr: else_expr.map(desugar_box),
+        },
+        ExpressionType::ForEachExpression {
+            iterator,
+            vector,
+            for_expr,
+        } => ExpressionType::ForEachExpression {
+            iterator,
+            vector: desugar_box(vector),
+            for_expr: desugar_box(for_expr),
+        },
+        ExpressionType::WhileExpression { condition, body } => ExpressionType::WhileExpression {
+            condition: desugar_box(condition),
+            body: desugar_box(body),
+        },
+        ExpressionType::UncheckedExpression { block } => ExpressionType::UncheckedExpression {
+            block: desugar_box(block),
+        },
+        ExpressionType::ParenExpression { expression } => ExpressionType::ParenExpression {
+            expression: desugar_box(expression),
+        },
+        ExpressionType::UnaryExpression { operator, operand } => ExpressionType::UnaryExpression {
+            operator,
+            operand: desugar_box(operand),
+        },
+        ExpressionType::Parameters { parameters } => ExpressionType::Parameters {
+            parameters: parameters.into_iter().map(desugar_stmt).collect(),
+        },
+        ExpressionType::Arguments { arguments } => ExpressionType::Arguments {
+            arguments: arguments.into_iter().map(desugar_expr).collect(),
+        },
+        ExpressionType::ArrayLiteral { elements } => ExpressionType::ArrayLiteral {
+            elements: elements.into_iter().map(desugar_expr).collect(),
+        },
+        ExpressionType::Range { start, end, step } => ExpressionType::Range {
+            start: desugar_box(start),
+            end: desugar_box(end),
+            step: step.map(desugar_box),
+        },
+        node => node,
+    })
 }
@@ -20,7 +20,7 @@ pub struct LexicalError {
 #[derive(Debug, PartialEq)]
 pub enum ParseErrorType {
     /// Parser encountered an unexpected end of input
-    EOF,
+    EOF(Option<String>),
     /// Parser encountered an extra token
     ExtraToken(Tok),
     /// Parser encountered an invalid token
@@ -34,6 +34,9 @@ pub enum ParseErrorType {
 #[derive(Debug, PartialEq)]
 pub enum LexicalErrorType {
     UnrecognizedToken { tok: char },
+    IdentifierTooLong { max: usize },
+    FloatingPointLiteral,
+    UnterminatedComment,
     OtherError(String),
 }
 
@@ -41,7 +44,7 @@ impl From<LalrpopError<Location, Tok, LexicalError>> for ParseError {
     fn from(err: LalrpopError<Location, Tok, LexicalError>) -> Self {
         match err {
             LalrpopError::InvalidToken { location } => ParseError {
-                error: ParseErrorType::EOF,
+                error: ParseErrorType::InvalidToken,
                 location,
             },
             LalrpopError::ExtraToken { token } => ParseError {
@@ -52,25 +55,27 @@ impl From<LalrpopError<Location, Tok, LexicalError>> for ParseError {
                 error: ParseErrorType::Lexical(error.error),
                 location: error.location,
             },
-            LalrpopError::UnrecognizedToken { token, expected } => {
-                let expected = if expected.len() == 1 {
-                    Some(expected[0].clone())
-                } else {
-                    None
-                };
-                ParseError {
-                    error: ParseErrorType::UnrecognizedToken(token.1, expected),
-                    location: token.0,
-                }
-            }
-            LalrpopError::UnrecognizedEOF { location, .. } => ParseError {
-                error: ParseErrorType::EOF,
+            LalrpopError::UnrecognizedToken { token, expected } => ParseError {
+                error: ParseErrorType::UnrecognizedToken(token.1, single_expected(expected)),
+                location: token.0,
+            },
+            LalrpopError::UnrecognizedEOF { location, expected } => ParseError {
+                error: ParseErrorType::EOF(single_expected(expected)),
                 location,
             },
         }
     }
 }
 
+/// The expected terminal, if the grammar allows only one.
+fn single_expected(expected: Vec<String>) -> Option<String> {
+    if expected.len() == 1 {
+        Some(expected[0].clone())
+    } else {
+        None
+    }
+}
+
 impl From<num_bigint::ParseBigIntError> for LexicalError {
     fn from(_err: num_bigint::ParseBigIntError) -> Self {
         LexicalError {
@@ -80,18 +85,60 @@ impl From<num_bigint::ParseBigIntError> for LexicalError {
     }
 }
 
+impl ParseError {
+    /// 1-based line of the error.
+    pub fn line(&self) -> usize {
+        self.location.row()
+    }
+
+    /// 1-based column of the error.
+    pub fn column(&self) -> usize {
+        self.location.column()
+    }
+
+    pub fn message(&self) -> String {
+        self.error.to_string()
+    }
+}
+
 impl fmt::Display for ParseError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{}", self.error)
+        write!(f, "{}:{}: {}", self.line(), self.column(), self.error)
     }
 }
 
 impl fmt::Display for ParseErrorType {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
+            ParseErrorType::EOF(Some(expected)) => write!(
+                f,
+                "Unexpected end of input, expected `{}`",
+                expected.trim_matches('"')
+            ),
+            ParseErrorType::EOF(None) => write!(f, "Unexpected end of input"),
             ParseErrorType::InvalidToken => write!(f, "Got invalid token"),
-            ParseErrorType::UnrecognizedToken(_tok, _opts) => write!(f, "Got unexpected token"),
-            _ => write!(f, "Got parser Error"),
+            ParseErrorType::UnrecognizedToken(tok, _opts) => {
+                write!(f, "Got unexpected token {:?}", tok)
+            }
+            ParseErrorType::ExtraToken(tok) => write!(f, "Got extraneous token {:?}", tok),
+            ParseErrorType::Lexical(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl fmt::Display for LexicalErrorType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LexicalErrorType::UnrecognizedToken { tok } => write!(f, "Got unexpected '{}'", tok),
+            LexicalErrorType::IdentifierTooLong { max } => {
+                write!(f, "Identifier is longer than {} characters", max)
+            }
+            LexicalErrorType::FloatingPointLiteral => write!(
+                f,
+                "Floating-point literals are not supported, only integer uint"
+            ),
+            LexicalErrorType::UnterminatedComment => write!(f, "Unterminated block comment"),
+            LexicalErrorType::OtherError(msg) => write!(f, "{}", msg),
         }
     }
 }
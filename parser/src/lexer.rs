@@ -7,18 +7,29 @@ pub type LexResult = Result<Spanned, LexicalError>;
 
 use num_bigint::BigUint;
 use std::collections::HashMap;
+use std::iter::Peekable;
 use std::str::FromStr;
 use unic_ucd_ident::{is_xid_continue, is_xid_start};
 
+pub const MAX_IDENTIFIER_LENGTH: usize = 1024;
+
 pub struct Lexer<T: Iterator<Item = char>> {
-    chars: T,
+    chars: Peekable<T>,
     location: Location,
     chr: Option<char>,
     keywords: HashMap<String, Tok>,
+    max_identifier_length: usize,
 }
 
 pub fn make_tokenizer<'a>(source: &'a str) -> impl Iterator<Item = LexResult> + 'a {
-    Lexer::new(source.chars())
+    make_tokenizer_with_max_identifier(source, MAX_IDENTIFIER_LENGTH)
+}
+
+pub fn make_tokenizer_with_max_identifier<'a>(
+    source: &'a str,
+    max_identifier_length: usize,
+) -> impl Iterator<Item = LexResult> + 'a {
+    Lexer::new(source.chars(), max_identifier_length)
 }
 
 fn get_keywords() -> HashMap<String, Tok> {
@@ -29,13 +40,25 @@ fn get_keywords() -> HashMap<String, Tok> {
     keywords.insert(String::from("bool"), Tok::Bool);
     keywords.insert(String::from("string"), Tok::String);
     keywords.insert(String::from("address"), Tok::Address);
+    keywords.insert(String::from("bytes"), Tok::Bytes);
     keywords.insert(String::from("function"), Tok::Function);
     keywords.insert(String::from("contract"), Tok::Contract);
     keywords.insert(String::from("if"), Tok::If);
     keywords.insert(String::from("else"), Tok::Else);
     keywords.insert(String::from("for"), Tok::For);
     keywords.insert(String::from("in"), Tok::In);
+    keywords.insert(String::from("while"), Tok::While);
     keywords.insert(String::from("returns"), Tok::Returns);
+    keywords.insert(String::from("return"), Tok::Return);
+    keywords.insert(String::from("constant"), Tok::Constant);
+    keywords.insert(String::from("immutable"), Tok::Immutable);
+    keywords.insert(String::from("using"), Tok::Using);
+    keywords.insert(String::from("step"), Tok::Step);
+    keywords.insert(String::from("unchecked"), Tok::Unchecked);
+    keywords.insert(String::from("public"), Tok::Public);
+    keywords.insert(String::from("private"), Tok::Private);
+    keywords.insert(String::from("true"), Tok::True);
+    keywords.insert(String::from("false"), Tok::False);
 
     keywords
 }
@@ -44,18 +67,20 @@ impl<T> Lexer<T>
 where
     T: Iterator<Item = char>,
 {
-    fn new(input: T) -> Self {
+    fn new(input: T, max_identifier_length: usize) -> Self {
         Lexer {
-            chars: input,
-            location: Location::new(0, 0),
+            chars: input.peekable(),
+            location: Location::new(1, 0),
             chr: None,
             keywords: get_keywords(),
+            max_identifier_length,
         }
     }
 
     fn next_token(&mut self) -> LexResult {
         if self.chr.is_none() {
             self.next_char();
+            self.skip_blank()?;
         }
         if let Some(c) = self.chr {
             let start = self.location;
@@ -65,7 +90,7 @@ where
                 self.consume_special_character(c)?
             };
             let end = self.location;
-            self.skip_blank();
+            self.skip_blank()?;
             Ok((start, token, end))
         } else {
             // End Of File
@@ -90,7 +115,7 @@ where
     }
 
     fn is_blank(&self, c: char) -> bool {
-        c == ' ' || c == '\n' || c == '\t'
+        c == ' ' || c == '\n' || c == '\t' || c == '\r'
     }
 
     fn is_identifier_continue(&self, c: char) -> bool {
@@ -100,19 +125,68 @@ where
         }
     }
 
-    fn skip_blank(&mut self) {
+    /// Skip blanks and comments, which produce no tokens.
+    fn skip_blank(&mut self) -> Result<(), LexicalError> {
         loop {
             if let Some(c) = self.chr {
                 if self.is_blank(c) {
                     self.next_char();
                     continue;
                 }
+                if c == '/' && self.chars.peek() == Some(&'/') {
+                    self.skip_line_comment();
+                    continue;
+                }
+                if c == '/' && self.chars.peek() == Some(&'*') {
+                    self.skip_block_comment()?;
+                    continue;
+                }
             }
             break;
         }
+        Ok(())
+    }
+
+    /// Skip a `/* */` comment. Block comments nest, so `/* a /* b */ c */` is one comment.
+    fn skip_block_comment(&mut self) -> Result<(), LexicalError> {
+        let start = self.location;
+        let mut depth = 0;
+        while let Some(c) = self.chr {
+            match (c, self.chars.peek()) {
+                ('/', Some('*')) => {
+                    depth += 1;
+                    self.next_char();
+                }
+                ('*', Some('/')) => {
+                    depth -= 1;
+                    self.next_char();
+                    if depth == 0 {
+                        self.next_char();
+                        return Ok(());
+                    }
+                }
+                _ => {}
+            }
+            self.next_char();
+        }
+        Err(LexicalError {
+            error: LexicalErrorType::UnterminatedComment,
+            location: start,
+        })
+    }
+
+    /// Skip a `//` comment up to the end of the line.
+    fn skip_line_comment(&mut self) {
+        while let Some(c) = self.chr {
+            if c == '\n' {
+                break;
+            }
+            self.next_char();
+        }
     }
 
     fn consume_identifier(&mut self, c: char) -> Result<Tok, LexicalError> {
+        let start = self.location;
         let mut text = String::new();
         text.push(c);
 
@@ -128,6 +202,22 @@ where
                 break;
             }
         }
+        if text.chars().count() > self.max_identifier_length {
+            return Err(LexicalError {
+                error: LexicalErrorType::IdentifierTooLong {
+                    max: self.max_identifier_length,
+                },
+                location: start,
+            });
+        }
+        if text == "hex" {
+            if let Some(quote @ '"') | Some(quote @ '\'') = self.chr {
+                return self.lex_hex_literal(quote, start);
+            }
+        }
+        if text == "pragma" {
+            return self.lex_pragma(start);
+        }
         if self.keywords.contains_key(&text) {
             Ok(self.keywords[&text].clone())
         } else {
@@ -135,6 +225,45 @@ where
         }
     }
 
+    fn lex_pragma(&mut self, start: Location) -> Result<Tok, LexicalError> {
+        self.skip_blank()?;
+        let location = self.location;
+        let key = match self.chr {
+            Some(c) if self.is_identifier_start(c) => self.consume_identifier(c)?,
+            _ => Tok::EOF,
+        };
+        let key = match key {
+            Tok::Identifier { name } => name,
+            _ => {
+                return Err(LexicalError {
+                    error: LexicalErrorType::OtherError(String::from("Expected a pragma name")),
+                    location,
+                })
+            }
+        };
+        // The value is free-form, e.g. a version like `0.1`, but ends on its line.
+        let mut value = String::new();
+        loop {
+            match (self.chr, self.chars.peek()) {
+                (Some(';'), _) => break,
+                (None, _) | (Some('\n'), _) | (Some('{'), _) | (Some('/'), Some('/' | '*')) => {
+                    return Err(LexicalError {
+                        error: LexicalErrorType::OtherError(String::from(
+                            "Expected `;` after pragma",
+                        )),
+                        location: start,
+                    })
+                }
+                (Some(c), _) => value.push(c),
+            }
+            self.next_char();
+        }
+        Ok(Tok::Pragma {
+            key,
+            value: value.trim().to_string(),
+        })
+    }
+
     fn consume_special_character(&mut self, c: char) -> Result<Tok, LexicalError> {
         match c {
             '0'..='9' => self.lex_number(c),
@@ -152,10 +281,14 @@ where
                 "<" => token = Some(Tok::Lt),
                 ">" => token = Some(Tok::Gt),
                 "=" => token = Some(Tok::Assign),
+                "!" => token = Some(Tok::Not),
+                // Only valid as the prefix of a two-character operator
+                "&" | "|" => token = None,
                 "+" => token = Some(Tok::Plus),
                 "-" => token = Some(Tok::Minus),
                 "*" => token = Some(Tok::Mul),
                 "/" => token = Some(Tok::Div),
+                "%" => token = Some(Tok::Mod),
                 "." => token = Some(Tok::Dot),
                 "," => {
                     token = Some(Tok::Comma);
@@ -169,6 +302,14 @@ where
                     token = Some(Tok::RBrace);
                     break;
                 }
+                "[" => {
+                    token = Some(Tok::LBracket);
+                    break;
+                }
+                "]" => {
+                    token = Some(Tok::RBracket);
+                    break;
+                }
                 "(" => {
                     token = Some(Tok::LPar);
                     break;
@@ -193,6 +334,34 @@ where
                     token = Some(Tok::Ge);
                     break;
                 }
+                "==" => {
+                    token = Some(Tok::Eq);
+                    break;
+                }
+                "+=" => {
+                    token = Some(Tok::PlusAssign);
+                    break;
+                }
+                "-=" => {
+                    token = Some(Tok::MinusAssign);
+                    break;
+                }
+                "*=" => {
+                    token = Some(Tok::MulAssign);
+                    break;
+                }
+                "/=" => {
+                    token = Some(Tok::DivAssign);
+                    break;
+                }
+                "%=" => {
+                    token = Some(Tok::ModAssign);
+                    break;
+                }
+                "!=" => {
+                    token = Some(Tok::NotEq);
+                    break;
+                }
                 "&&" => {
                     token = Some(Tok::And);
                     break;
@@ -227,6 +396,14 @@ where
     }
 
     fn lex_number(&mut self, c: char) -> Result<Tok, LexicalError> {
+        if c == '0' {
+            match self.chars.peek() {
+                Some(&prefix @ ('x' | 'X')) => return self.lex_radix_number(prefix, 16),
+                Some(&prefix @ ('b' | 'B')) => return self.lex_radix_number(prefix, 2),
+                _ => {}
+            }
+        }
+        let start = self.location;
         let mut text = String::new();
         text.push(c);
         loop {
@@ -234,6 +411,13 @@ where
             if let Some(c) = self.chr {
                 match c {
                     '0'..='9' => text.push(c),
+                    // `1.5` or `1e3`, but not the range `1..5`
+                    '.' | 'e' | 'E' if self.chars.peek().is_some_and(char::is_ascii_digit) => {
+                        return Err(LexicalError {
+                            error: LexicalErrorType::FloatingPointLiteral,
+                            location: start,
+                        });
+                    }
                     '_' => {
                         self.next_char();
                         if let Some(c) = self.chr {
@@ -263,7 +447,71 @@ where
         })
     }
 
+    /// Lex a `0x` or `0b` number, with `_` allowed between digits.
+    fn lex_radix_number(&mut self, prefix: char, radix: u32) -> Result<Tok, LexicalError> {
+        let start = self.location;
+        // Skip the prefix
+        self.next_char();
+        self.next_char();
+        let mut text = String::new();
+        while let Some(c) = self.chr {
+            if c.is_digit(radix) {
+                text.push(c);
+            } else if c == '_' && !text.is_empty() {
+                if !self.chars.peek().is_some_and(|next| next.is_digit(radix)) {
+                    return Err(LexicalError {
+                        error: LexicalErrorType::UnrecognizedToken { tok: c },
+                        location: self.location,
+                    });
+                }
+            } else if self.is_identifier_continue(c) {
+                return Err(LexicalError {
+                    error: LexicalErrorType::UnrecognizedToken { tok: c },
+                    location: self.location,
+                });
+            } else {
+                break;
+            }
+            self.next_char();
+        }
+        match BigUint::parse_bytes(text.as_bytes(), radix) {
+            Some(number) => Ok(Tok::Num { number }),
+            // No digits after the prefix
+            None => Err(LexicalError {
+                error: LexicalErrorType::UnrecognizedToken { tok: prefix },
+                location: start,
+            }),
+        }
+    }
+
     fn lex_literal(&mut self, c: char) -> Result<Tok, LexicalError> {
+        Ok(Tok::Literal {
+            literal: self.consume_literal(c),
+        })
+    }
+
+    fn lex_hex_literal(&mut self, c: char, start: Location) -> Result<Tok, LexicalError> {
+        let location = self.location;
+        let literal = self.consume_literal(c);
+        if let Some(tok) = literal.chars().find(|c| !c.is_ascii_hexdigit()) {
+            return Err(LexicalError {
+                error: LexicalErrorType::UnrecognizedToken { tok },
+                location,
+            });
+        }
+        // Each byte takes two digits.
+        if !literal.len().is_multiple_of(2) {
+            return Err(LexicalError {
+                error: LexicalErrorType::OtherError(String::from(
+                    "Hex literal must have an even number of digits",
+                )),
+                location: start,
+            });
+        }
+        Ok(Tok::HexLiteral { literal })
+    }
+
+    fn consume_literal(&mut self, c: char) -> String {
         let mut text = String::new();
         let first = c;
         loop {
@@ -279,7 +527,7 @@ where
                 break;
             }
         }
-        Ok(Tok::Literal { literal: text })
+        text
     }
 }
 
@@ -36,6 +36,6 @@ impl Location {
 
     pub fn new_line(&mut self) {
         self.row += 1;
-        self.column = 1;
+        self.column = 0;
     }
 }
@@ -1,18 +1,52 @@
 use crate::ast;
-use crate::error::ParseError;
+use crate::error::{ParseError, ParseErrorType};
 use crate::lexer;
+use crate::token::Tok;
 use crate::zwrap;
 
 macro_rules! do_lalr_parsing {
     ($input: expr, $parser: ident) => {{
         let lxr = lexer::make_tokenizer($input);
         match zwrap::$parser::new().parse(lxr) {
-            Err(err) => Err(ParseError::from(err)),
+            Err(err) => {
+                // Only trust the open delimiter where the grammar could actually close it.
+                let closing = match &err {
+                    lalrpop_util::ParseError::UnrecognizedEOF { expected, .. } => {
+                        unclosed_delimiter($input).filter(|closing| expected.contains(closing))
+                    }
+                    _ => None,
+                };
+                let mut error = ParseError::from(err);
+                if let (ParseErrorType::EOF(expected), Some(closing)) = (&mut error.error, closing)
+                {
+                    *expected = Some(closing);
+                }
+                Err(error)
+            }
             Ok(top) => Ok(top),
         }
     }};
 }
 
+/// The delimiter closing the innermost block still open at the end of `source`.
+/// Input cut short inside a block reports it rather than whatever else could follow.
+fn unclosed_delimiter(source: &str) -> Option<String> {
+    let mut open = vec![];
+    for token in lexer::make_tokenizer(source) {
+        match token {
+            Ok((_, Tok::LBrace, _)) => open.push("\"}\""),
+            Ok((_, Tok::LPar, _)) => open.push("\")\""),
+            Ok((_, Tok::LBracket, _)) => open.push("\"]\""),
+            Ok((_, Tok::RBrace, _)) | Ok((_, Tok::RPar, _)) | Ok((_, Tok::RBracket, _)) => {
+                open.pop();
+            }
+            Ok(_) => {}
+            Err(_) => return None,
+        }
+    }
+    open.pop().map(String::from)
+}
+
 pub fn parse_expression(source: &str) -> Result<ast::Expression, ParseError> {
     do_lalr_parsing!(source, ExpressionParser)
 }
@@ -21,6 +55,10 @@ pub fn parse_statement(source: &str) -> Result<ast::Statement, ParseError> {
     do_lalr_parsing!(source, StatementParser)
 }
 
+pub fn parse_type(source: &str) -> Result<ast::Type, ParseError> {
+    do_lalr_parsing!(source, TypeParser)
+}
+
 pub fn parse_program(source: &str) -> Result<ast::Program, ParseError> {
     do_lalr_parsing!(source, ProgramParser)
 }
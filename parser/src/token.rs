@@ -9,13 +9,20 @@ pub enum Tok {
     Minus,
     Mul,
     Div,
+    Mod,
     // Power Operator
     Pow,
     // Assign operator
     Assign,
+    PlusAssign,
+    MinusAssign,
+    MulAssign,
+    DivAssign,
+    ModAssign,
     // Logical Operator
     And,
     Or,
+    Not,
     // Comparison Operator
     Lt,
     Le,
@@ -32,6 +39,7 @@ pub enum Tok {
     Bool,
     String,
     Address,
+    Bytes,
 
     // Keyword
     Function,
@@ -40,12 +48,27 @@ pub enum Tok {
     Else,
     For,
     In,
+    While,
     Returns,
+    Return,
+    Constant,
+    Immutable,
+    Using,
+    Step,
+    Unchecked,
+    Public,
+    Private,
+    True,
+    False,
+    // `pragma key value`, lexed up to the `;`
+    Pragma { key: String, value: String },
     // Mark
     LPar,
     RPar,
     LBrace,
     RBrace,
+    LBracket,
+    RBracket,
     Semi,
     Comma,
     Dot,
@@ -53,6 +76,7 @@ pub enum Tok {
     // variable
     Num { number: BigUint },
     Literal { literal: String },
+    HexLiteral { literal: String },
     Identifier { name: String },
     EOF,
 }
@@ -0,0 +1,70 @@
+use num_bigint::BigUint;
+use ziraffe_parser::ast::{
+    desugar, Expression, ExpressionType, Located, Operator, Program, StatementType,
+};
+use ziraffe_parser::location::Location;
+use ziraffe_parser::parser;
+
+#[test]
+fn test_located_map() {
+    let number = Located {
+        location: Location::new(2, 5),
+        node: 3,
+    };
+    let doubled = number.map(|n| n * 2);
+    assert_eq!(doubled.location, Location::new(2, 5));
+    assert_eq!(doubled.node, 6);
+
+    let expr = parser::parse_expression("abc").unwrap();
+    let location = expr.location;
+    let name = expr.map(|node| node.identifier_name());
+    assert_eq!(name.location, location);
+    assert_eq!(name.node, Some(String::from("abc")));
+}
+
+fn located<T>(node: T) -> Located<T> {
+    Located {
+        location: Location::default(),
+        node,
+    }
+}
+
+fn identifier(name: &str) -> Box<Expression> {
+    Box::new(located(ExpressionType::Identifier {
+        value: String::from(name),
+    }))
+}
+
+fn one() -> Box<Expression> {
+    Box::new(located(ExpressionType::Number {
+        value: BigUint::from(1u32),
+    }))
+}
+
+fn program(expression: ExpressionType) -> Program {
+    Program::GlobalStatements(vec![located(StatementType::Expression {
+        expression: Box::new(located(expression)),
+    })])
+}
+
+#[test]
+fn test_desugar_compound_assign() {
+    // a += 1
+    let sugared = program(ExpressionType::AssignExpression {
+        left: identifier("a"),
+        operator: Operator::Add,
+        right: one(),
+    });
+    // a = a + 1
+    let expected = program(ExpressionType::AssignExpression {
+        left: identifier("a"),
+        operator: Operator::Assign,
+        right: Box::new(located(ExpressionType::BinaryExpression {
+            left: identifier("a"),
+            operator: Operator::Add,
+            right: one(),
+        })),
+    });
+    assert_eq!(desugar(sugared), expected);
+    assert_eq!(desugar(expected.clone()), expected);
+}
@@ -1,4 +1,8 @@
+use num_bigint::BigUint;
+use ziraffe_parser::ast::{self, Type};
+use ziraffe_parser::error::{LexicalErrorType, ParseErrorType};
 use ziraffe_parser::parser;
+use ziraffe_parser::token::Tok;
 
 #[test]
 fn test_if_expression_parser() {
@@ -6,10 +10,33 @@ fn test_if_expression_parser() {
     assert!(parser::parse_expression("b = if a < 2 { 1 } else { 0 }").is_ok());
 }
 
+#[test]
+fn test_else_if_parser() {
+    let expr = parser::parse_expression("if a < 2 { 1 } else if a < 4 { 2 } else { 3 }").unwrap();
+    let else_expr = match expr.node {
+        ast::ExpressionType::IfExpression {
+            else_expr: Some(else_expr),
+            ..
+        } => else_expr,
+        node => panic!("expected IfExpression, got {:?}", node),
+    };
+    assert!(matches!(
+        else_expr.node,
+        ast::ExpressionType::IfExpression {
+            else_expr: Some(_),
+            ..
+        }
+    ));
+    assert!(parser::parse_expression("if a < 2 { 1 } else if a < 4 { 2 }").is_ok());
+    assert!(parser::parse_expression("if a < 2 { 1 } else if { 2 }").is_err());
+}
+
 #[test]
 fn test_for_expression_parser() {
     assert!(parser::parse_expression("for i in 1..10 { a = a + 1; }").is_ok());
     assert!(parser::parse_expression("for _ in 0..9 { b = b + 2; }").is_ok());
+    assert!(parser::parse_expression("for i in a..n { b = b + i; }").is_ok());
+    assert!(parser::parse_expression("for i in a + 1..n * 2 { b = b + i; }").is_ok());
 }
 
 #[test]
@@ -25,3 +52,441 @@ fn test_init_statement_parser() {
     assert!(parser::parse_statement("uint a = b").is_ok());
     assert!(parser::parse_statement("uint a = 1").is_ok());
 }
+
+#[test]
+fn test_hex_literal_parser() {
+    assert!(parser::parse_statement("bytes data = hex\"00ff\"").is_ok());
+    assert!(parser::parse_statement("bytes data = hex'deadbeef'").is_ok());
+    assert!(parser::parse_statement("bytes data = hex\"0g\"").is_err());
+}
+
+#[test]
+fn test_type_parser() {
+    assert_eq!(parser::parse_type("uint"), Ok(Type::Uint));
+    assert_eq!(parser::parse_type("address"), Ok(Type::Address));
+    assert_eq!(parser::parse_type("bytes"), Ok(Type::Bytes));
+    assert!(parser::parse_type("a").is_err());
+}
+
+#[test]
+fn test_function_call_parser() {
+    assert!(parser::parse_statement("f()").is_ok());
+    assert!(parser::parse_statement("a = f(1, b) + 2").is_ok());
+    assert!(parser::parse_statement("a = (1 + 2) * 3").is_ok());
+}
+
+#[test]
+fn test_trailing_token_parser() {
+    let err = parser::parse_program("contract A {} xyz").unwrap_err();
+    assert_eq!(
+        err.error,
+        ParseErrorType::UnrecognizedToken(
+            Tok::Identifier {
+                name: String::from("xyz")
+            },
+            None
+        )
+    );
+    assert_eq!(err.location.column(), 15);
+    assert_eq!(
+        err.to_string(),
+        "1:15: Got unexpected token Identifier { name: \"xyz\" }"
+    );
+    let err = parser::parse_program("contract A {} )").unwrap_err();
+    assert_eq!(err.location.column(), 15);
+}
+
+#[test]
+fn test_function_returns_parser() {
+    assert!(parser::parse_program("contract A { function f() returns (uint) {} }").is_ok());
+    assert!(parser::parse_program("contract A { function g(uint a) returns (bool) {} }").is_ok());
+    assert!(parser::parse_program("contract A { function h() {} returns (uint) }").is_err());
+}
+
+#[test]
+fn test_floating_point_parser() {
+    let err = parser::parse_statement("uint a = 1.5").unwrap_err();
+    assert_eq!(
+        err.error,
+        ParseErrorType::Lexical(LexicalErrorType::FloatingPointLiteral)
+    );
+    assert_eq!(
+        err.to_string(),
+        "1:10: Floating-point literals are not supported, only integer uint"
+    );
+    assert!(parser::parse_statement("uint a = 1e3").is_err());
+    assert!(parser::parse_expression("for i in 1..5 { a = a + 1; }").is_ok());
+}
+
+#[test]
+fn test_member_mutability_parser() {
+    assert!(
+        parser::parse_program("contract A { uint constant a = 1; address immutable b; }").is_ok()
+    );
+    assert!(parser::parse_program("contract A { function f() { uint constant a = 1; } }").is_err());
+}
+
+#[test]
+fn test_parse_error_position() {
+    let err = parser::parse_program("contract A {\n  uint a = ;\n}").unwrap_err();
+    assert_eq!(err.line(), 2);
+    assert_eq!(err.column(), 12);
+    assert_eq!(err.message(), "Got unexpected token Semi");
+    assert_eq!(err.to_string(), "2:12: Got unexpected token Semi");
+}
+
+// Debug output of the AST without its locations.
+fn without_locations(program: &ast::Program) -> String {
+    let debug = format!("{:?}", program);
+    let mut stripped = String::new();
+    let mut rest = debug.as_str();
+    while let Some(start) = rest.find("location: Location {") {
+        stripped.push_str(&rest[..start]);
+        rest = &rest[start..];
+        rest = &rest[rest.find('}').unwrap() + 1..];
+    }
+    stripped.push_str(rest);
+    stripped
+}
+
+#[test]
+fn test_multi_line_parser() {
+    let single =
+        parser::parse_program("contract A { function f(uint a, uint b) returns (uint) { a + b } }")
+            .unwrap();
+    let multi = parser::parse_program(
+        "\r\n  contract A {\r\n    function f(\n        uint a,\n        uint b\n    )\n    returns (uint)\n    {\n        a\n        + b\n    }\n}\n",
+    )
+    .unwrap();
+    assert_eq!(without_locations(&single), without_locations(&multi));
+}
+
+#[test]
+fn test_using_parser() {
+    assert!(parser::parse_program("contract A { using Math for uint; }").is_ok());
+    assert!(parser::parse_statement("x.double()").is_ok());
+    assert!(parser::parse_statement("a = f(1).add(2, b) + 1").is_ok());
+    assert!(parser::parse_program("contract A { using Math; }").is_err());
+}
+
+#[test]
+fn test_for_step_parser() {
+    let expr = parser::parse_expression("for i in 0..10 { }").unwrap();
+    assert!(matches!(
+        expr.node,
+        ast::ExpressionType::ForEachExpression { vector, .. }
+            if matches!(vector.node, ast::ExpressionType::Range { step: None, .. })
+    ));
+    assert!(parser::parse_expression("for i in 0..10 step { }").is_err());
+    let expr = parser::parse_expression("for i in 0..10 step 2 { }").unwrap();
+    if let ast::ExpressionType::ForEachExpression { vector, .. } = expr.node {
+        if let ast::ExpressionType::Range { step, .. } = vector.node {
+            assert!(step.is_some());
+            return;
+        }
+    }
+    panic!("expected a range with a step");
+}
+
+#[test]
+fn test_pragma_parser() {
+    let program = parser::parse_program("pragma ziraffe 0.1;\ncontract A {}").unwrap();
+    let ast::Program::GlobalStatements(statements) = program;
+    assert_eq!(
+        statements[0].node,
+        ast::StatementType::PragmaStatement {
+            key: String::from("ziraffe"),
+            value: String::from("0.1"),
+        }
+    );
+    assert!(parser::parse_program("pragma ;").is_err());
+    assert!(parser::parse_program("pragma ziraffe 0.1").is_err());
+
+    for source in [
+        "pragma ziraffe 0.1 contract A {}",
+        "pragma ziraffe 0.1\ncontract A {}",
+        "pragma ziraffe 0.1 // version\n;",
+    ] {
+        let err = parser::parse_program(source).unwrap_err();
+        assert_eq!(err.message(), "Expected `;` after pragma", "{:?}", source);
+        assert_eq!((err.location.row(), err.column()), (1, 1), "{:?}", source);
+    }
+}
+
+#[test]
+fn test_unchecked_parser() {
+    assert!(parser::parse_statement("unchecked { a = a + 1; }").is_ok());
+    assert!(parser::parse_statement("unchecked a = a + 1").is_err());
+}
+
+#[test]
+fn test_unexpected_eof_parser() {
+    let err = parser::parse_program("contract A { uint b;").unwrap_err();
+    assert_eq!(err.error, ParseErrorType::EOF(Some(String::from("\"}\""))));
+    assert_eq!(
+        err.to_string(),
+        "1:20: Unexpected end of input, expected `}`"
+    );
+    let err = parser::parse_program("contract A { function f() { a = 1").unwrap_err();
+    assert!(matches!(err.error, ParseErrorType::EOF(_)));
+    assert_eq!(err.column(), 33);
+    let err = parser::parse_program("contract A { function f(uint a").unwrap_err();
+    assert_eq!(err.message(), "Unexpected end of input, expected `)`");
+    let err = parser::parse_program("contract A").unwrap_err();
+    assert_eq!(err.message(), "Unexpected end of input");
+    // The open block is not reported where something else must come first.
+    // `;` or an array size may follow the type.
+    let err = parser::parse_program("contract A { using Math for uint").unwrap_err();
+    assert_eq!(err.error, ParseErrorType::EOF(None));
+    let err = parser::parse_program("contract A { uint b =").unwrap_err();
+    assert_eq!(err.message(), "Unexpected end of input");
+    let err = parser::parse_program("contract A { function f() returns").unwrap_err();
+    assert_eq!(err.message(), "Unexpected end of input, expected `(`");
+    assert!(matches!(
+        parser::parse_statement("uint a =").unwrap_err().error,
+        ParseErrorType::EOF(_)
+    ));
+}
+
+#[test]
+fn test_member_visibility_parser() {
+    let program = parser::parse_program("contract A { public uint balance; }").unwrap();
+    let ast::Program::GlobalStatements(statements) = program;
+    if let ast::StatementType::ContractStatement { members, .. } = &statements[0].node {
+        if let ast::StatementType::MemberStatement { statements } = &members.node {
+            if let ast::StatementType::InitializerStatement { visibility, .. } = &statements[0].node
+            {
+                assert_eq!(*visibility, ast::Visibility::Public);
+                return;
+            }
+        }
+    }
+    panic!("expected a member initializer");
+}
+
+#[test]
+fn test_double_else_parser() {
+    let err = parser::parse_statement("if c {} else {} else {}").unwrap_err();
+    assert_eq!(
+        err.error,
+        ParseErrorType::Lexical(LexicalErrorType::OtherError(String::from(
+            "An `if` can have only one `else` branch"
+        )))
+    );
+    assert_eq!(err.column(), 17);
+    assert!(parser::parse_statement("if c {} else {}").is_ok());
+    assert!(
+        parser::parse_program("contract A { function f() { if c {} else {} else {}; } }").is_err()
+    );
+}
+
+#[test]
+fn test_mod_parser() {
+    let expr = parser::parse_expression("a = b % 3").unwrap();
+    if let ast::ExpressionType::AssignExpression { right, .. } = expr.node {
+        if let ast::ExpressionType::BinaryExpression { operator, .. } = right.node {
+            assert_eq!(operator, ast::Operator::Mod);
+            return;
+        }
+    }
+    panic!("expected a modulo assignment");
+}
+
+#[test]
+fn test_compound_assign_parser() {
+    for (source, expected) in [
+        ("a += 1", ast::Operator::Add),
+        ("a -= 1", ast::Operator::Sub),
+        ("a *= 1", ast::Operator::Mul),
+        ("a /= 1", ast::Operator::Div),
+        ("a %= 1", ast::Operator::Mod),
+        ("a = 1", ast::Operator::Assign),
+    ] {
+        match parser::parse_expression(source).unwrap().node {
+            ast::ExpressionType::AssignExpression { operator, .. } => {
+                assert_eq!(operator, expected)
+            }
+            node => panic!("expected an assignment for {}, got {:?}", source, node),
+        }
+    }
+    assert!(parser::parse_expression("a + = 1").is_err());
+}
+
+#[test]
+fn test_unary_parser() {
+    let expr = parser::parse_expression("-b * 2").unwrap();
+    let ast::ExpressionType::BinaryExpression { left, .. } = expr.node else {
+        panic!("expected `-b * 2` to be a multiplication");
+    };
+    assert!(matches!(
+        left.node,
+        ast::ExpressionType::UnaryExpression {
+            operator: ast::UnaryOperator::Neg,
+            ..
+        }
+    ));
+    assert!(parser::parse_expression("!flag && !(a < b)").is_ok());
+    assert!(parser::parse_expression("a - -b").is_ok());
+    assert!(parser::parse_expression("a != !b").is_ok());
+    assert!(parser::parse_expression("a !").is_err());
+}
+
+#[test]
+fn test_line_comment_parser() {
+    let commented = parser::parse_program(
+        "// Adds numbers\ncontract A { // members\n    uint a = 1; // initial\n    function f(uint b) returns (uint) {\n        // comment only\n        a + b // sum\n    }\n}\n// trailing",
+    )
+    .unwrap();
+    let plain = parser::parse_program(
+        "contract A { uint a = 1; function f(uint b) returns (uint) { a + b } }",
+    )
+    .unwrap();
+    assert_eq!(without_locations(&commented), without_locations(&plain));
+}
+
+#[test]
+fn test_block_comment_parser() {
+    assert!(
+        parser::parse_program("/* A\n * contract\n */\ncontract A { uint /* x */ a; }").is_ok()
+    );
+    assert_eq!(
+        parser::parse_program("contract A { /* uint a; }")
+            .unwrap_err()
+            .to_string(),
+        "1:14: Unterminated block comment"
+    );
+}
+
+#[test]
+fn test_hex_number_parser() {
+    let expr = parser::parse_expression("a = 0x1f").unwrap();
+    let ast::ExpressionType::AssignExpression { right, .. } = expr.node else {
+        panic!("expected an assignment");
+    };
+    assert_eq!(
+        right.node,
+        ast::ExpressionType::Number {
+            value: BigUint::from(31u32)
+        }
+    );
+    let err = parser::parse_expression("a = 0x").unwrap_err();
+    assert_eq!(
+        err.error,
+        ParseErrorType::Lexical(LexicalErrorType::UnrecognizedToken { tok: 'x' })
+    );
+    assert_eq!(err.column(), 5);
+    let err = parser::parse_expression("a = 0xG1").unwrap_err();
+    assert_eq!(
+        err.error,
+        ParseErrorType::Lexical(LexicalErrorType::UnrecognizedToken { tok: 'G' })
+    );
+    assert_eq!(err.column(), 7);
+}
+
+#[test]
+fn test_binary_number_parser() {
+    for source in ["a = 0b1010_1010", "a = 0B10101010", "a = 0XaA"] {
+        let expr = parser::parse_expression(source).unwrap();
+        let ast::ExpressionType::AssignExpression { right, .. } = expr.node else {
+            panic!("expected an assignment for {}", source);
+        };
+        assert_eq!(
+            right.node,
+            ast::ExpressionType::Number {
+                value: BigUint::from(170u32)
+            }
+        );
+    }
+    let err = parser::parse_expression("a = 0b").unwrap_err();
+    assert_eq!(
+        err.error,
+        ParseErrorType::Lexical(LexicalErrorType::UnrecognizedToken { tok: 'b' })
+    );
+    assert_eq!(err.column(), 5);
+    let err = parser::parse_expression("a = 0b102").unwrap_err();
+    assert_eq!(
+        err.error,
+        ParseErrorType::Lexical(LexicalErrorType::UnrecognizedToken { tok: '2' })
+    );
+    assert_eq!(err.column(), 9);
+    assert!(parser::parse_expression("a = 0b1010_").is_err());
+}
+
+#[test]
+fn test_bool_literal_parser() {
+    let stmt = parser::parse_statement("bool b = true").unwrap();
+    let ast::StatementType::InitializerStatement { default, .. } = stmt.node else {
+        panic!("expected an initializer");
+    };
+    assert_eq!(
+        default.unwrap().node,
+        ast::ExpressionType::Bool { value: true }
+    );
+    assert!(parser::parse_expression("a = !false && b").is_ok());
+    assert!(parser::parse_statement("uint true = 1").is_err());
+}
+
+#[test]
+fn test_while_parser() {
+    let expr = parser::parse_expression("while a < 10 { a = a + 1; }").unwrap();
+    assert!(matches!(
+        expr.node,
+        ast::ExpressionType::WhileExpression { .. }
+    ));
+    assert!(parser::parse_program(
+        "contract A { function f() { uint a = 0; while a < 10 { a += 1; }; } }"
+    )
+    .is_ok());
+    assert!(parser::parse_expression("while { }").is_err());
+    assert!(parser::parse_expression("while a < 10 a = 1").is_err());
+}
+
+#[test]
+fn test_return_parser() {
+    let stmt = parser::parse_statement("return a + 1").unwrap();
+    assert!(matches!(
+        stmt.node,
+        ast::StatementType::Return { value: Some(_) }
+    ));
+    let stmt = parser::parse_statement("return").unwrap();
+    assert!(matches!(
+        stmt.node,
+        ast::StatementType::Return { value: None }
+    ));
+}
+
+#[test]
+fn test_array_parser() {
+    assert_eq!(
+        parser::parse_type("uint[3]"),
+        Ok(Type::Array(Box::new(Type::Uint), 3))
+    );
+    assert_eq!(
+        parser::parse_type("bool[2][4]"),
+        Ok(Type::Array(
+            Box::new(Type::Array(Box::new(Type::Bool), 2)),
+            4
+        ))
+    );
+    assert!(parser::parse_type("uint[]").is_err());
+    assert!(parser::parse_type("uint[a]").is_err());
+
+    let stmt = parser::parse_statement("uint[3] xs = [1, 2, 3]").unwrap();
+    if let ast::StatementType::InitializerStatement {
+        default: Some(default),
+        ..
+    } = stmt.node
+    {
+        if let ast::ExpressionType::ArrayLiteral { elements } = default.node {
+            assert_eq!(elements.len(), 3);
+            return;
+        }
+    }
+    panic!("expected an array initializer");
+}
+
+#[test]
+fn test_unclosed_array_parser() {
+    let err = parser::parse_expression("[1, 2").unwrap_err();
+    assert_eq!(err.message(), "Unexpected end of input, expected `]`");
+}
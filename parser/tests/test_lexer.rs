@@ -0,0 +1,161 @@
+use num_bigint::BigUint;
+use ziraffe_parser::error::LexicalErrorType;
+use ziraffe_parser::lexer::{
+    make_tokenizer, make_tokenizer_with_max_identifier, Tok, MAX_IDENTIFIER_LENGTH,
+};
+use ziraffe_parser::location::Location;
+
+fn tokens(source: &str) -> Vec<Tok> {
+    make_tokenizer(source)
+        .map(|token| token.unwrap().1)
+        .collect()
+}
+
+fn name(name: &str) -> Tok {
+    Tok::Identifier {
+        name: String::from(name),
+    }
+}
+
+#[test]
+fn test_comparison_operator_lexer() {
+    assert_eq!(tokens("a <= b"), vec![name("a"), Tok::Le, name("b")]);
+    assert_eq!(tokens("a >= b"), vec![name("a"), Tok::Ge, name("b")]);
+    assert_eq!(tokens("a != b"), vec![name("a"), Tok::NotEq, name("b")]);
+    assert_eq!(tokens("a == b"), vec![name("a"), Tok::Eq, name("b")]);
+    assert_eq!(tokens("a<=b"), vec![name("a"), Tok::Le, name("b")]);
+    assert_eq!(tokens("a==b"), vec![name("a"), Tok::Eq, name("b")]);
+}
+
+#[test]
+fn test_power_operator_lexer() {
+    assert_eq!(tokens("a ** b"), vec![name("a"), Tok::Pow, name("b")]);
+    assert_eq!(tokens("a * b"), vec![name("a"), Tok::Mul, name("b")]);
+    assert_eq!(tokens("a**b"), vec![name("a"), Tok::Pow, name("b")]);
+}
+
+#[test]
+fn test_logical_operator_lexer() {
+    assert_eq!(tokens("a && b"), vec![name("a"), Tok::And, name("b")]);
+    assert_eq!(tokens("a || b"), vec![name("a"), Tok::Or, name("b")]);
+}
+
+#[test]
+fn test_identifier_length_lexer() {
+    let name = "a".repeat(MAX_IDENTIFIER_LENGTH);
+    assert!(make_tokenizer(&name).all(|token| token.is_ok()));
+
+    let source = format!("b = {}", "a".repeat(MAX_IDENTIFIER_LENGTH + 1));
+    let err = make_tokenizer(&source)
+        .find_map(|token| token.err())
+        .unwrap();
+    assert_eq!(
+        err.error,
+        LexicalErrorType::IdentifierTooLong {
+            max: MAX_IDENTIFIER_LENGTH
+        }
+    );
+    assert_eq!(err.location.column(), 5);
+
+    assert!(make_tokenizer_with_max_identifier("abcd", 3).any(|token| token.is_err()));
+}
+
+#[test]
+fn test_hex_number_lexer() {
+    assert_eq!(
+        tokens("0xff"),
+        vec![Tok::Num {
+            number: BigUint::from(255u32)
+        }]
+    );
+    assert_eq!(
+        tokens("0x0"),
+        vec![Tok::Num {
+            number: BigUint::from(0u32)
+        }]
+    );
+    let error = make_tokenizer("0xfg").find_map(Result::err).unwrap();
+    assert_eq!(
+        error.error,
+        LexicalErrorType::UnrecognizedToken { tok: 'g' }
+    );
+}
+
+#[test]
+fn test_binary_number_lexer() {
+    let number = |n: u32| Tok::Num {
+        number: BigUint::from(n),
+    };
+    assert_eq!(tokens("0b1010"), vec![number(10)]);
+    assert_eq!(tokens("0b1111_0000"), vec![number(240)]);
+    assert_eq!(tokens("0xff_ff"), vec![number(0xffff)]);
+    let error = make_tokenizer("0b").find_map(Result::err).unwrap();
+    assert_eq!(
+        error.error,
+        LexicalErrorType::UnrecognizedToken { tok: 'b' }
+    );
+    let error = make_tokenizer("0b102").find_map(Result::err).unwrap();
+    assert_eq!(
+        error.error,
+        LexicalErrorType::UnrecognizedToken { tok: '2' }
+    );
+    let error = make_tokenizer("0b1_").find_map(Result::err).unwrap();
+    assert_eq!(
+        error.error,
+        LexicalErrorType::UnrecognizedToken { tok: '_' }
+    );
+}
+
+#[test]
+fn test_hex_literal_lexer() {
+    assert_eq!(
+        tokens("hex\"00ff\""),
+        vec![Tok::HexLiteral {
+            literal: String::from("00ff")
+        }]
+    );
+    let err = make_tokenizer("a = hex'0f0'")
+        .find_map(|token| token.err())
+        .unwrap();
+    assert_eq!(
+        err.error,
+        LexicalErrorType::OtherError(String::from(
+            "Hex literal must have an even number of digits"
+        ))
+    );
+    assert_eq!(err.location, Location::new(1, 5));
+}
+
+#[test]
+fn test_line_comment_lexer() {
+    assert_eq!(tokens("a // b c"), vec![name("a")]);
+    assert_eq!(tokens("a// b\nc"), vec![name("a"), name("c")]);
+    assert_eq!(tokens("a / b"), vec![name("a"), Tok::Div, name("b")]);
+    assert_eq!(
+        tokens("\"http://a\" // b"),
+        vec![Tok::Literal {
+            literal: String::from("http://a")
+        }]
+    );
+    let located: Vec<_> = make_tokenizer("// one\n  a // two\nb")
+        .map(|token| token.unwrap().0)
+        .collect();
+    assert_eq!(located, vec![Location::new(2, 3), Location::new(3, 1)]);
+}
+
+#[test]
+fn test_block_comment_lexer() {
+    assert_eq!(tokens("a /* b */ c"), vec![name("a"), name("c")]);
+    assert_eq!(tokens("a/**/*b"), vec![name("a"), Tok::Mul, name("b")]);
+    assert_eq!(tokens("a /* x /* y */ z */ b"), vec![name("a"), name("b")]);
+    let located: Vec<_> = make_tokenizer("/* one\n   two */ a /*\n*/\n  b")
+        .map(|token| token.unwrap().0)
+        .collect();
+    assert_eq!(located, vec![Location::new(2, 11), Location::new(4, 3)]);
+
+    let err = make_tokenizer("a\n  /* open /* nested */ b")
+        .find_map(|token| token.err())
+        .unwrap();
+    assert_eq!(err.error, LexicalErrorType::UnterminatedComment);
+    assert_eq!(err.location, Location::new(2, 3));
+}